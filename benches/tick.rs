@@ -0,0 +1,55 @@
+//! Native timings for `Universe::tick` across board sizes. Run with
+//! `cargo bench`; it prints the mean time per generation with the reused
+//! buffers next to the same tick allocating a new board every generation,
+//! as it did before, first for plain Life and then with a stochastic rule.
+//! Finally it shows how a sparse board fares with tile skipping.
+extern crate wasm_game_of_life;
+
+use std::time::{Duration, Instant};
+
+use wasm_game_of_life::Universe;
+
+/// Mean time of `f` over enough runs to fill about `budget`.
+fn time<F: FnMut()>(budget: Duration, mut f: F) -> Duration {
+    f();
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < budget {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs.max(1)
+}
+
+fn main() {
+    let budget = Duration::from_millis(500);
+    for &survival in [1.0, 0.95].iter() {
+        println!("{:>6} {:>14} {:>14}  survival {}", "size", "reused", "allocated", survival);
+        for &size in [64, 128, 256, 512, 1024].iter() {
+            // Both variants start from the same board, as it thins out with
+            // every generation.
+            let start = || {
+                let mut universe = Universe::random(size, size, 0.5, 42);
+                universe.set_survival_probability(survival);
+                universe
+            };
+            let mut universe = start();
+            let reused = time(budget, || universe.tick());
+            let mut universe = start();
+            let allocated = time(budget, || {
+                universe.drop_spare_buffers();
+                universe.tick();
+            });
+            println!("{:>6} {:>14?} {:>14?}", size, reused, allocated);
+        }
+    }
+
+    // Mostly empty boards, where stable tiles are skipped.
+    println!("{:>6} {:>14} {:>14}", "size", "glider tick", "tiles skipped");
+    for &size in [256, 1024].iter() {
+        let mut universe = Universe::with_size(size, size);
+        universe.insert_glider(size / 2, size / 2).unwrap();
+        let tick = time(budget, || universe.tick());
+        println!("{:>6} {:>14?} {:>8}/{}", size, tick, universe.skipped_tiles(), universe.tile_count());
+    }
+}
@@ -0,0 +1,46 @@
+use crate::Universe;
+
+impl Universe {
+    /// Note that the byte `cells()` holds for cell `i` has changed, once
+    /// per cell until `reset_changed`, and wake its tile.
+    pub(crate) fn mark_changed(&mut self, i: usize) {
+        if self.changed_mark.len() != self.cells.len() {
+            self.clear_changed();
+        }
+        self.mark_tile(i);
+        if !self.changed_mark[i] {
+            self.changed_mark[i] = true;
+            self.changed.push(i as u32);
+        }
+    }
+
+    /// Mark every cell that differs from the generation just swapped out
+    /// into the spare buffer.
+    pub(crate) fn record_swapped(&mut self) {
+        if self.states.is_empty() {
+            for i in 0..self.cells.len() {
+                if self.cells[i] != self.next_cells[i] {
+                    self.mark_changed(i);
+                }
+            }
+        } else {
+            for i in 0..self.states.len() {
+                if self.states[i] != self.next_states[i] {
+                    self.mark_changed(i);
+                }
+            }
+        }
+    }
+
+    /// Forget every recorded change, sizing the marks to the board.
+    pub(crate) fn clear_changed(&mut self) {
+        if self.changed_mark.len() == self.cells.len() {
+            for &i in self.changed.iter() {
+                self.changed_mark[i as usize] = false;
+            }
+        } else {
+            self.changed_mark = vec![false; self.cells.len()];
+        }
+        self.changed.clear();
+    }
+}
@@ -0,0 +1,81 @@
+//! The Life 1.06 format: a header followed by one `x y` pair per live cell,
+//! with `y` growing downwards.
+//!
+//! ```text
+//! #Life 1.06
+//! 0 -1
+//! 1 0
+//! -1 1
+//! 0 1
+//! 1 1
+//! ```
+use std::fmt::Write;
+
+use super::{Pattern, PatternError};
+
+const HEADER: &str = "#Life 1.06";
+
+/// The pattern is normalised to its bounding box, whose top-left corner in
+/// the file's coordinates is kept in `Pattern::x` and `Pattern::y`.
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut lines = text.lines().enumerate().map(|(n, line)| (n + 1, line.trim()));
+    match lines.next() {
+        Some((_, HEADER)) => {}
+        _ => return Err(PatternError::MissingHeader),
+    }
+
+    let mut pattern = Pattern::default();
+    let mut coordinates = Vec::new();
+    for (number, line) in lines {
+        if let Some(comment) = line.strip_prefix("#D") {
+            pattern.comments.push(comment.trim().to_string());
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace().map(str::parse::<i32>);
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => coordinates.push((x, y)),
+            _ => return Err(PatternError::InvalidCoordinates { line: number }),
+        }
+    }
+
+    if let (Some(min_x), Some(min_y)) = (
+        coordinates.iter().map(|c| c.0).min(),
+        coordinates.iter().map(|c| c.1).min(),
+    ) {
+        let max_x = coordinates.iter().map(|c| c.0).max().unwrap();
+        let max_y = coordinates.iter().map(|c| c.1).max().unwrap();
+        // Spans are worked out in `i64`, since they can exceed `i32`.
+        let width = max_x as i64 - min_x as i64 + 1;
+        let height = max_y as i64 - min_y as i64 + 1;
+        if width > u32::MAX as i64 || height > u32::MAX as i64 {
+            return Err(PatternError::TooLarge {
+                width: width as u64,
+                height: height as u64,
+            });
+        }
+        pattern.x = min_x;
+        pattern.y = min_y;
+        pattern.width = width as u32;
+        pattern.height = height as u32;
+        pattern.cells = coordinates
+            .into_iter()
+            .map(|(x, y)| ((y as i64 - min_y as i64) as u32, (x as i64 - min_x as i64) as u32))
+            .collect();
+    }
+    Ok(pattern)
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", HEADER).unwrap();
+    for comment in pattern.comments.iter() {
+        writeln!(out, "#D {}", comment).unwrap();
+    }
+    for &(row, col) in pattern.cells.iter() {
+        writeln!(out, "{} {}", pattern.x + col as i32, pattern.y + row as i32).unwrap();
+    }
+    out
+}
@@ -0,0 +1,142 @@
+//! Golly's macrocell format, which serialises a HashLife quadtree directly.
+//!
+//! After the `[M2]` header and `#` comment lines, every line defines one
+//! node. An 8x8 leaf is written as rows of `.` and `*`, each ending in `$`.
+//! A larger node is written as `k nw ne sw se`: its size is `2^k` and its
+//! quadrants are 1-based line numbers of earlier nodes, with 0 for empty.
+//!
+//! ```text
+//! [M2] (golly 4.0)
+//! #R B3/S23
+//! $$$$.*$..*$***$
+//! 4 0 0 0 1
+//! ```
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::PatternError;
+use crate::hashlife::{HashLife, NodeId};
+use crate::Rule;
+
+const HEADER: &str = "[M2]";
+const LEAF_LEVEL: u8 = 3;
+const LEAF_SIZE: usize = 1 << LEAF_LEVEL;
+
+pub fn parse(text: &str) -> Result<HashLife, PatternError> {
+    let mut lines = text.lines().enumerate().map(|(n, line)| (n + 1, line.trim()));
+    match lines.next() {
+        Some((_, line)) if line.starts_with(HEADER) => {}
+        _ => return Err(PatternError::MissingHeader),
+    }
+
+    let mut rule = Rule::default();
+    let mut body = Vec::new();
+    for (number, line) in lines {
+        if let Some(value) = line.strip_prefix("#R") {
+            rule = value.trim().parse()?;
+        } else if !line.is_empty() && !line.starts_with('#') {
+            body.push((number, line));
+        }
+    }
+
+    let mut life = HashLife::with_rule(rule)?;
+    // `nodes[i]` is the node defined on the i-th body line; index 0 is empty.
+    let mut nodes: Vec<Option<(NodeId, u8)>> = vec![None];
+    for (number, line) in body {
+        let invalid = || PatternError::InvalidNode { line: number };
+        let node = if line.starts_with(|c: char| c.is_ascii_digit()) {
+            let fields: Vec<usize> = line
+                .split_whitespace()
+                .map(|f| f.parse().map_err(|_| invalid()))
+                .collect::<Result<_, _>>()?;
+            let (level, children) = match fields.as_slice() {
+                [level, children @ ..] if children.len() == 4 && *level > LEAF_LEVEL as usize && *level < 63 => {
+                    (*level as u8, children)
+                }
+                _ => return Err(invalid()),
+            };
+            let mut quads = [0; 4];
+            for (quad, &child) in quads.iter_mut().zip(children) {
+                *quad = match nodes.get(child).ok_or_else(invalid)? {
+                    Some((id, child_level)) if *child_level == level - 1 => *id,
+                    Some(_) => return Err(invalid()),
+                    None => life.empty_node(level - 1),
+                };
+            }
+            (life.join(quads[0], quads[1], quads[2], quads[3]), level)
+        } else {
+            let mut grid = [[false; LEAF_SIZE]; LEAF_SIZE];
+            let (mut row, mut col) = (0, 0);
+            for c in line.chars() {
+                match c {
+                    '.' | '*' if row < LEAF_SIZE && col < LEAF_SIZE => {
+                        grid[row][col] = c == '*';
+                        col += 1;
+                    }
+                    '$' => {
+                        row += 1;
+                        col = 0;
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+            let alive = |x: u64, y: u64| grid[y as usize][x as usize];
+            (life.build(LEAF_LEVEL, 0, 0, (LEAF_SIZE as u64, LEAF_SIZE as u64), &alive), LEAF_LEVEL)
+        };
+        nodes.push(Some(node));
+    }
+
+    match nodes.last() {
+        Some(Some((root, _))) => life.set_root(*root),
+        _ => return Err(PatternError::MissingHeader),
+    }
+    Ok(life)
+}
+
+pub fn write(life: &HashLife) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", HEADER).unwrap();
+    writeln!(out, "#R {}", life.rule_ref()).unwrap();
+    let mut numbers = HashMap::new();
+    if write_node(life, life.root(), &mut numbers, &mut out) == 0 {
+        // The root needs a line of its own even when the plane is empty.
+        match life.node(life.root()).level {
+            LEAF_LEVEL => out.push_str("$\n"),
+            level => writeln!(out, "{} 0 0 0 0", level).unwrap(),
+        }
+    }
+    out
+}
+
+/// Write `id` after its children, returning its line number (0 if empty).
+fn write_node(life: &HashLife, id: NodeId, numbers: &mut HashMap<NodeId, usize>, out: &mut String) -> usize {
+    let node = life.node(id);
+    if node.population == 0 {
+        return 0;
+    }
+    if let Some(&number) = numbers.get(&id) {
+        return number;
+    }
+
+    if node.level == LEAF_LEVEL {
+        let size = LEAF_SIZE as u64;
+        let rows: Vec<String> = (0..size)
+            .map(|y| {
+                let row: String = (0..size).map(|x| if life.get_in(id, x, y) { '*' } else { '.' }).collect();
+                row.trim_end_matches('.').to_string()
+            })
+            .collect();
+        let used = rows.iter().rposition(|row| !row.is_empty()).map_or(0, |last| last + 1);
+        for row in rows.iter().take(used) {
+            out.push_str(row);
+            out.push('$');
+        }
+    } else {
+        let children = [node.nw, node.ne, node.sw, node.se].map(|q| write_node(life, q, numbers, out));
+        write!(out, "{} {} {} {} {}", node.level, children[0], children[1], children[2], children[3]).unwrap();
+    }
+    out.push('\n');
+    let number = numbers.len() + 1;
+    numbers.insert(id, number);
+    number
+}
@@ -0,0 +1,223 @@
+//! Readers and writers for the pattern file formats used by Golly and the
+//! LifeWiki. Every format parses into a `Pattern`, which can then be turned
+//! into a `Universe` or placed into an existing one.
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{AntRule, CoordinateError, HashLife, MargolusRule, Mode, Rule, RuleError, Universe, WireCell};
+
+pub mod life106;
+pub mod macrocell;
+pub mod plaintext;
+pub mod rle;
+
+/// Largest number of cells `Universe::from_pattern` and
+/// `Universe::from_macrocell` will allocate.
+pub const MAX_FLAT_CELLS: u64 = 1 << 26;
+
+/// A pattern as read from a file: its bounding box, live cells and metadata.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pattern {
+    pub width: u32,
+    pub height: u32,
+    /// Position of the top-left corner, for formats such as Life 1.06 that
+    /// use absolute coordinates.
+    pub x: i32,
+    pub y: i32,
+    /// Rulestring from the file, if it named one.
+    pub rule: Option<String>,
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    /// `(row, col)` of each live cell, relative to the top-left corner.
+    pub cells: Vec<(u32, u32)>,
+    /// State of each cell in `cells` for multi-state patterns, such as
+    /// WireWorld or Generations ones; empty when every cell is simply
+    /// alive. Formats without states treat every listed cell as alive.
+    pub states: Vec<u8>,
+}
+
+impl Pattern {
+    /// Add a cell in state `state`, where 1 is alive.
+    pub fn push_cell(&mut self, row: u32, col: u32, state: u8) {
+        if state != 1 || !self.states.is_empty() {
+            self.states.resize(self.cells.len(), 1);
+            self.states.push(state);
+        }
+        self.cells.push((row, col));
+    }
+
+    /// The state of the `i`th cell in `cells`.
+    pub fn state(&self, i: usize) -> u8 {
+        self.states.get(i).cloned().unwrap_or(1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// The `x = .., y = ..` header line is missing.
+    MissingHeader,
+    /// The header line could not be parsed.
+    InvalidHeader(String),
+    /// A character that has no meaning in the format was found.
+    UnexpectedChar { line: usize, found: char },
+    /// A line that should hold an `x y` coordinate pair does not.
+    InvalidCoordinates { line: usize },
+    /// A macrocell node line is malformed or refers to a missing node.
+    InvalidNode { line: usize },
+    /// The pattern is too large to flatten into a `Universe`.
+    TooLarge { width: u64, height: u64 },
+    /// A cell lies outside the size given in the header.
+    OutOfBounds { row: u32, col: u32 },
+    /// A cell has a state the universe's rule does not have.
+    InvalidState { state: u8, states: u8 },
+    Rule(RuleError),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::MissingHeader => write!(f, "missing \"x = .., y = ..\" header"),
+            PatternError::InvalidHeader(header) => write!(f, "invalid header \"{}\"", header),
+            PatternError::UnexpectedChar { line, found } => {
+                write!(f, "unexpected character '{}' on line {}", found, line)
+            }
+            PatternError::InvalidCoordinates { line } => write!(f, "invalid coordinates on line {}", line),
+            PatternError::InvalidNode { line } => write!(f, "invalid node on line {}", line),
+            PatternError::TooLarge { width, height } => {
+                write!(f, "a {}x{} pattern is too large to flatten", width, height)
+            }
+            PatternError::OutOfBounds { row, col } => {
+                write!(f, "cell at row {}, column {} lies outside the pattern size", row, col)
+            }
+            PatternError::InvalidState { state, states } => {
+                write!(f, "state {} is out of range for a rule with {} states", state, states)
+            }
+            PatternError::Rule(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+impl From<RuleError> for PatternError {
+    fn from(err: RuleError) -> PatternError {
+        PatternError::Rule(err)
+    }
+}
+
+impl From<CoordinateError> for PatternError {
+    fn from(err: CoordinateError) -> PatternError {
+        PatternError::OutOfBounds {
+            row: err.row as u32,
+            col: err.col as u32,
+        }
+    }
+}
+
+impl From<PatternError> for JsValue {
+    fn from(err: PatternError) -> JsValue {
+        js_sys::Error::new(&err.to_string()).into()
+    }
+}
+
+impl Universe {
+    /// A dead universe exactly the size of `pattern`, running its rule, with
+    /// the pattern's cells set. A `WireWorld` rule selects WireWorld mode
+    /// and a turmite rule such as `RL` turmite mode, without any ants. A
+    /// Margolus rule selects Margolus mode in phase 0. Patterns with more
+    /// than `MAX_FLAT_CELLS` cells are rejected.
+    pub fn from_pattern(pattern: &Pattern) -> Result<Universe, PatternError> {
+        let (width, height) = (pattern.width as u64, pattern.height as u64);
+        if width * height > MAX_FLAT_CELLS {
+            return Err(PatternError::TooLarge { width, height });
+        }
+        let mut universe = Universe::with_size(pattern.width.max(1), pattern.height.max(1));
+        match pattern.rule.as_deref().map(str::trim) {
+            Some(rule) if rule.eq_ignore_ascii_case(WireCell::RULE) => universe.set_mode(Mode::WireWorld),
+            Some(rule) if AntRule::matches(rule) => universe.set_ant_rule(rule.parse::<AntRule>()?),
+            Some(rule) if MargolusRule::matches(rule) => universe.set_margolus_rule(rule.parse::<MargolusRule>()?),
+            Some(rule) => universe.replace_rule(rule.parse::<Rule>()?),
+            None => {}
+        }
+        universe.place_pattern(pattern, 0, 0)?;
+        Ok(universe)
+    }
+
+    /// Flatten a quadtree into a universe just large enough for the bounding
+    /// box of its live cells, as long as that has at most `max_cells` cells.
+    pub fn from_hashlife(life: &HashLife, max_cells: u64) -> Result<Universe, PatternError> {
+        let bounds = life.bounds().unwrap_or_else(|| vec![0, 0, 1, 1]);
+        let (width, height) = (bounds[2] as u64, bounds[3] as u64);
+        if width.saturating_mul(height) > max_cells {
+            return Err(PatternError::TooLarge { width, height });
+        }
+        Ok(life.to_universe(bounds[0], bounds[1], width as u32, height as u32))
+    }
+
+    /// Fail if a cell of `pattern` has a state at or above `state_count`.
+    fn check_states(&self, pattern: &Pattern) -> Result<(), PatternError> {
+        let states = self.state_count();
+        match (0..pattern.cells.len()).map(|n| pattern.state(n)).find(|&state| state >= states) {
+            Some(state) => Err(PatternError::InvalidState { state, states }),
+            None => Ok(()),
+        }
+    }
+
+    /// Set the cells of `pattern` to their states with its top-left corner
+    /// at `(row, col)`. Fails without changing anything if a cell lies
+    /// outside the universe or has a state the current rule does not have.
+    pub fn place_pattern(&mut self, pattern: &Pattern, row: u32, col: u32) -> Result<(), PatternError> {
+        self.check_states(pattern)?;
+        let indices = pattern
+            .cells
+            .iter()
+            .map(|&(r, c)| self.checked_index(row as i64 + r as i64, col as i64 + c as i64))
+            .collect::<Result<Vec<usize>, CoordinateError>>()?;
+        for (n, i) in indices.into_iter().enumerate() {
+            self.write_state(i, pattern.state(n));
+        }
+        Ok(())
+    }
+
+    /// Set the cells of `pattern` to their states with its top-left corner
+    /// at `(row, col)`. Cells past the edges are mapped through the
+    /// topology, and dropped if they fall off a dead edge. Fails without
+    /// changing anything if a cell has a state the current rule does not
+    /// have.
+    pub fn place_pattern_wrapped(&mut self, pattern: &Pattern, row: i64, col: i64) -> Result<(), PatternError> {
+        self.check_states(pattern)?;
+        for (n, &(r, c)) in pattern.cells.iter().enumerate() {
+            if let Some(i) = self.resolve(row + r as i64, col + c as i64) {
+                self.write_state(i, pattern.state(n));
+            }
+        }
+        Ok(())
+    }
+
+    /// The whole universe as a pattern, with no name or comments. Only
+    /// live cells are kept, except in WireWorld and turmite modes and under
+    /// Generations rules, where every non-empty cell is kept with its state.
+    /// Ants are not kept.
+    pub fn to_pattern(&self) -> Pattern {
+        let width = self.width as usize;
+        let mut pattern = Pattern {
+            width: self.width,
+            height: self.height,
+            rule: Some(self.rule()),
+            ..Pattern::default()
+        };
+        let generations = self.mode == Mode::Life && self.rule.states() > 2;
+        for (i, state) in self.get_states().into_iter().enumerate() {
+            let (row, col) = ((i / width) as u32, (i % width) as u32);
+            match self.mode {
+                _ if generations && state != 0 => pattern.push_cell(row, col, state),
+                Mode::WireWorld | Mode::Turmite if state != 0 => pattern.push_cell(row, col, state),
+                Mode::Life | Mode::Margolus if state == 1 => pattern.push_cell(row, col, state),
+                _ => {}
+            }
+        }
+        pattern
+    }
+}
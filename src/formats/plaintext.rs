@@ -0,0 +1,93 @@
+//! The plaintext `.cells` format, e.g. for a glider:
+//!
+//! ```text
+//! !Name: Glider
+//! .O.
+//! ..O
+//! OOO
+//! ```
+//!
+//! WireWorld patterns use `#` for conductors, `@` for electron heads and
+//! `~` for electron tails; any of them marks the pattern as WireWorld.
+use std::fmt::Write;
+
+use super::{Pattern, PatternError};
+use crate::WireCell;
+
+/// Characters for each `WireCell` state, in state order.
+const WIRE_CHARS: [char; 4] = ['.', '@', '~', '#'];
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut rows = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if let Some(comment) = line.strip_prefix('!') {
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                pattern.author = Some(author.trim().to_string());
+            } else {
+                pattern.comments.push(comment.strip_prefix(' ').unwrap_or(comment).to_string());
+            }
+            continue;
+        }
+        let row = rows.len() as u32;
+        for (col, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => pattern.cells.push((row, col as u32)),
+                '@' | '~' | '#' => {
+                    let state = WIRE_CHARS.iter().position(|&w| w == c).unwrap() as u8;
+                    pattern.push_cell(row, col as u32, state);
+                    pattern.rule = Some(WireCell::RULE.to_string());
+                }
+                found => return Err(PatternError::UnexpectedChar { line: number + 1, found }),
+            }
+        }
+        rows.push(line.chars().count() as u32);
+    }
+
+    // Blank lines after the last row do not add to the height.
+    while rows.last() == Some(&0) {
+        rows.pop();
+    }
+    pattern.height = rows.len() as u32;
+    pattern.width = rows.into_iter().max().unwrap_or(0);
+    Ok(pattern)
+}
+
+/// Rows are written at the full pattern width so that the size survives a
+/// round trip.
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        writeln!(out, "!Name: {}", name).unwrap();
+    }
+    if let Some(author) = &pattern.author {
+        writeln!(out, "!Author: {}", author).unwrap();
+    }
+    for comment in pattern.comments.iter() {
+        if comment.is_empty() {
+            out.push_str("!\n");
+        } else {
+            writeln!(out, "! {}", comment).unwrap();
+        }
+    }
+
+    let wireworld = pattern.rule.as_deref() == Some(WireCell::RULE);
+    let mut grid = vec![vec!['.'; pattern.width as usize]; pattern.height as usize];
+    for (i, &(row, col)) in pattern.cells.iter().enumerate() {
+        grid[row as usize][col as usize] = match pattern.state(i) {
+            state if wireworld => WIRE_CHARS[state as usize % WIRE_CHARS.len()],
+            1 => 'O',
+            _ => '.',
+        };
+    }
+    for line in grid {
+        out.extend(line);
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,197 @@
+//! The run-length encoded format, e.g. for a glider:
+//!
+//! ```text
+//! #N Glider
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+//!
+//! Multi-state patterns use `.` for state 0 and `A` to `X` for states 1 to
+//! 24, as Golly does, e.g. a WireWorld diode tail, head and conductor are
+//! `BAC`. Higher states take a prefix from `p` to `y`, each standing for
+//! another 24 states, so `pA` is 25, `qA` 49 and `yO` 255.
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+use super::{Pattern, PatternError};
+
+/// Lines written by `write` are wrapped before this many characters.
+const LINE_LENGTH: usize = 70;
+
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::default();
+    let mut lines = text.lines().enumerate();
+
+    // Comment lines, up to and including the header.
+    loop {
+        let (_, line) = lines.next().ok_or(PatternError::MissingHeader)?;
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next();
+            let value = chars.as_str().trim().to_string();
+            match kind {
+                Some('N') => pattern.name = Some(value),
+                Some('O') => pattern.author = Some(value),
+                Some('C') | Some('c') => pattern.comments.push(value),
+                Some('r') => pattern.rule = Some(value),
+                _ => {}
+            }
+        } else if !line.is_empty() {
+            parse_header(line, &mut pattern)?;
+            break;
+        }
+    }
+
+    let (mut row, mut col) = (0u32, 0u32);
+    let mut count: Option<u32> = None;
+    'lines: for (number, line) in lines {
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap();
+                    count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                    continue;
+                }
+                'b' | '.' => {
+                    col = col.checked_add(count.unwrap_or(1)).ok_or(PatternError::OutOfBounds { row, col: u32::MAX })?;
+                }
+                'o' | 'A'..='X' | 'p'..='y' => {
+                    let state = match c {
+                        'o' => 1,
+                        'A'..='X' => c as u8 - b'A' + 1,
+                        _ => match chars.next() {
+                            Some(letter @ 'A'..='X') => {
+                                let state = 24 * (c as u32 - 'p' as u32 + 1) + (letter as u32 - 'A' as u32 + 1);
+                                u8::try_from(state)
+                                    .map_err(|_| PatternError::UnexpectedChar { line: number + 1, found: letter })?
+                            }
+                            _ => return Err(PatternError::UnexpectedChar { line: number + 1, found: c }),
+                        },
+                    };
+                    for _ in 0..count.unwrap_or(1) {
+                        if row >= pattern.height || col >= pattern.width {
+                            return Err(PatternError::OutOfBounds { row, col });
+                        }
+                        pattern.push_cell(row, col, state);
+                        // Cannot overflow, since `col` is below the width.
+                        col += 1;
+                    }
+                }
+                '$' => {
+                    row = row.checked_add(count.unwrap_or(1)).ok_or(PatternError::OutOfBounds { row: u32::MAX, col })?;
+                    col = 0;
+                }
+                '!' => break 'lines,
+                c if c.is_whitespace() => {}
+                found => return Err(PatternError::UnexpectedChar { line: number + 1, found }),
+            }
+            count = None;
+        }
+    }
+    Ok(pattern)
+}
+
+/// Parse `x = 3, y = 3, rule = B3/S23`. The rule is taken verbatim up to the
+/// end of the line, since some rulestrings contain commas.
+fn parse_header(line: &str, pattern: &mut Pattern) -> Result<(), PatternError> {
+    let invalid = || PatternError::InvalidHeader(line.to_string());
+    let (size, rule) = match line.find("rule") {
+        Some(i) => (&line[..i], Some(&line[i..])),
+        None => (line, None),
+    };
+
+    let (mut width, mut height) = (None, None);
+    for field in size.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+        let value = value.trim().parse::<u32>().map_err(|_| invalid())?;
+        match key.trim() {
+            "x" => width = Some(value),
+            "y" => height = Some(value),
+            _ => return Err(invalid()),
+        }
+    }
+    pattern.width = width.ok_or_else(invalid)?;
+    pattern.height = height.ok_or_else(invalid)?;
+
+    if let Some(rule) = rule {
+        let (_, value) = rule.split_once('=').ok_or_else(invalid)?;
+        pattern.rule = Some(value.trim().to_string());
+    }
+    Ok(())
+}
+
+pub fn write(pattern: &Pattern) -> String {
+    let mut out = String::new();
+    if let Some(name) = &pattern.name {
+        writeln!(out, "#N {}", name).unwrap();
+    }
+    if let Some(author) = &pattern.author {
+        writeln!(out, "#O {}", author).unwrap();
+    }
+    for comment in pattern.comments.iter() {
+        writeln!(out, "#C {}", comment).unwrap();
+    }
+    write!(out, "x = {}, y = {}", pattern.width, pattern.height).unwrap();
+    if let Some(rule) = &pattern.rule {
+        write!(out, ", rule = {}", rule).unwrap();
+    }
+    out.push('\n');
+
+    let multistate = !pattern.states.is_empty();
+    let dead = if multistate { "." } else { "b" };
+    // Cells pushed with state 0 are dead, and written as gaps.
+    let mut cells: Vec<((u32, u32), u8)> = (0..pattern.cells.len())
+        .map(|i| (pattern.cells[i], pattern.state(i)))
+        .filter(|&(_, state)| state != 0)
+        .collect();
+    cells.sort_unstable();
+    cells.dedup_by_key(|&mut (position, _)| position);
+
+    let mut line = String::new();
+    let mut push = |out: &mut String, run: u32, tag: &str| {
+        let token = if run == 1 { tag.to_string() } else { format!("{}{}", run, tag) };
+        if line.len() + token.len() > LINE_LENGTH {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    };
+
+    let (mut row, mut col) = (0, 0);
+    let mut cells = cells.into_iter().peekable();
+    while let Some(((r, c), state)) = cells.next() {
+        if r > row {
+            push(&mut out, r - row, "$");
+            row = r;
+            col = 0;
+        }
+        if c > col {
+            push(&mut out, c - col, dead);
+        }
+        let mut end = c + 1;
+        while cells.peek() == Some(&((r, end), state)) {
+            cells.next();
+            end += 1;
+        }
+        push(&mut out, end - c, &tag(state, multistate));
+        col = end;
+    }
+    push(&mut out, 1, "!");
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+/// The letters for a live `state`: `o` in a two-state pattern, otherwise
+/// `A` to `X`, with a `p` to `y` prefix above 24.
+fn tag(state: u8, multistate: bool) -> String {
+    let letter = |n: u8| (b'A' + (n - 1) % 24) as char;
+    match state {
+        _ if !multistate => "o".to_string(),
+        1..=24 => letter(state).to_string(),
+        _ => format!("{}{}", (b'p' + (state - 25) / 24) as char, letter(state - 24)),
+    }
+}
@@ -0,0 +1,573 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Rule, RuleError, Universe};
+
+pub(crate) type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Once the arena grows past this many nodes, everything not reachable from
+/// the root is dropped before the next step.
+const GC_THRESHOLD: usize = 1 << 21;
+
+/// Deepest root the tree may grow to. Its side, `2^MAX_LEVEL`, and its
+/// origin must both fit in an `i64`.
+const MAX_LEVEL: u8 = 62;
+
+/// Largest `k` that `HashLife::step` accepts: the root has to reach level
+/// `k + 2` and then grow once more.
+pub const MAX_STEP: u8 = MAX_LEVEL - 3;
+
+/// Why `HashLife::step`, `HashLife::advance` or `HashLife::set_cell` could
+/// not run.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StepError {
+    /// The step, the pattern's extent or a cell's position needs a tree
+    /// deeper than 64-bit coordinates allow.
+    TooLarge,
+    /// The generation counter would overflow.
+    GenerationOverflow,
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StepError::TooLarge => write!(f, "step or pattern is too large for 64-bit coordinates"),
+            StepError::GenerationOverflow => write!(f, "generation count would overflow"),
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+impl From<StepError> for JsValue {
+    fn from(err: StepError) -> JsValue {
+        js_sys::Error::new(&err.to_string()).into()
+    }
+}
+
+/// A canonical quadtree node. Level `k` covers a `2^k x 2^k` square; level 0
+/// nodes are the two single cells `DEAD` and `ALIVE`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Node {
+    pub(crate) nw: NodeId,
+    pub(crate) ne: NodeId,
+    pub(crate) sw: NodeId,
+    pub(crate) se: NodeId,
+    pub(crate) level: u8,
+    pub(crate) population: u64,
+}
+
+/// Gosper's HashLife: a hash-consed quadtree with memoised successors, able
+/// to advance a pattern by `2^k` generations in time roughly proportional to
+/// how much distinct structure the pattern has, not to `k` or to its area.
+///
+/// Unlike `Universe` the plane is unbounded, so coordinates are signed with
+/// `x` growing rightwards (a `Universe` column) and `y` downwards (a row).
+#[wasm_bindgen]
+pub struct HashLife {
+    nodes: Vec<Node>,
+    index: HashMap<(NodeId, NodeId, NodeId, NodeId), NodeId>,
+    /// `empty[k]` is the all-dead node of level `k`.
+    empty: Vec<NodeId>,
+    /// Memoised `successor(node, j)` results.
+    results: HashMap<(NodeId, u8), NodeId>,
+    rule: Rule,
+    root: NodeId,
+    /// Coordinates of the root's top-left cell.
+    origin_x: i64,
+    origin_y: i64,
+    generation: u64,
+}
+
+impl HashLife {
+    /// An empty plane running `rule`. Generations and non-totalistic rules
+    /// are rejected, as are rules with `B0` since they would make the
+    /// infinite empty background flash on and off.
+    pub fn with_rule(rule: Rule) -> Result<HashLife, RuleError> {
+        let rule = rule.require_life_like()?;
+        if rule.birth()[0] {
+            return Err(RuleError::Unsupported(rule.to_string()));
+        }
+        let leaf = |population| Node {
+            nw: DEAD,
+            ne: DEAD,
+            sw: DEAD,
+            se: DEAD,
+            level: 0,
+            population,
+        };
+        let mut life = HashLife {
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            empty: vec![DEAD],
+            results: HashMap::new(),
+            rule,
+            root: DEAD,
+            origin_x: 0,
+            origin_y: 0,
+            generation: 0,
+        };
+        life.root = life.empty_node(3);
+        Ok(life)
+    }
+
+    /// Build a plane from a flat row-major `width * height` slice of cells,
+    /// as returned by `Universe::get_cells`. The cell at index `row * width
+    /// + col` lands at `(x, y) = (col, row)`.
+    pub fn from_cells(width: u32, height: u32, cells: &[Cell], rule: Rule) -> Result<HashLife, RuleError> {
+        let mut life = HashLife::with_rule(rule)?;
+        let mut level = 3;
+        while (1u64 << level) < width.max(height) as u64 {
+            level += 1;
+        }
+        let alive = |x: u64, y: u64| cells[(y * width as u64 + x) as usize] == Cell::Alive;
+        life.root = life.build(level, 0, 0, (width as u64, height as u64), &alive);
+        Ok(life)
+    }
+
+    /// Copy the `width x height` region with top-left corner `(x, y)` into a
+    /// flat row-major vector, the same layout as `Universe::get_cells`.
+    pub fn get_cells(&self, x: i64, y: i64, width: u32, height: u32) -> Vec<Cell> {
+        let mut cells = vec![Cell::Dead; width as usize * height as usize];
+        let region = Region { x, y, width, height };
+        self.fill(self.root, self.origin_x, self.origin_y, &region, &mut cells);
+        cells
+    }
+
+    pub(crate) fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    pub(crate) fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// Replace the whole pattern with `root`, centred on the origin.
+    pub(crate) fn set_root(&mut self, root: NodeId) {
+        self.root = root;
+        let half = 1i64 << (self.level() - 1);
+        self.origin_x = -half;
+        self.origin_y = -half;
+    }
+
+    pub(crate) fn rule_ref(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn level(&self) -> u8 {
+        self.node(self.root).level
+    }
+
+    pub(crate) fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        if let Some(&id) = self.index.get(&(nw, ne, sw, se)) {
+            return id;
+        }
+        let population = [nw, ne, sw, se]
+            .iter()
+            .fold(0u64, |sum, &q| sum.saturating_add(self.node(q).population));
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node {
+            nw,
+            ne,
+            sw,
+            se,
+            level: self.node(nw).level + 1,
+            population,
+        });
+        self.index.insert((nw, ne, sw, se), id);
+        id
+    }
+
+    pub(crate) fn empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level as usize]
+    }
+
+    /// Build the level `level` node with top-left cell `(x, y)` from
+    /// `alive`, which is only asked about cells inside `size`. Everything
+    /// past `size` is dead, and quadrants lying wholly beyond it are shared
+    /// empty nodes rather than built cell by cell.
+    pub(crate) fn build(&mut self, level: u8, x: u64, y: u64, size: (u64, u64), alive: &dyn Fn(u64, u64) -> bool) -> NodeId {
+        if x >= size.0 || y >= size.1 {
+            return self.empty_node(level);
+        }
+        if level == 0 {
+            return if alive(x, y) { ALIVE } else { DEAD };
+        }
+        let half = 1u64 << (level - 1);
+        let nw = self.build(level - 1, x, y, size, alive);
+        let ne = self.build(level - 1, x + half, y, size, alive);
+        let sw = self.build(level - 1, x, y + half, size, alive);
+        let se = self.build(level - 1, x + half, y + half, size, alive);
+        self.join(nw, ne, sw, se)
+    }
+
+    fn fill(&self, id: NodeId, x: i64, y: i64, region: &Region, out: &mut [Cell]) {
+        let node = self.node(id);
+        let size = 1i64 << node.level;
+        if node.population == 0 || !region.overlaps(x, y, size) {
+            return;
+        }
+        if node.level == 0 {
+            let i = (y - region.y) as usize * region.width as usize + (x - region.x) as usize;
+            out[i] = Cell::Alive;
+            return;
+        }
+        let half = size / 2;
+        self.fill(node.nw, x, y, region, out);
+        self.fill(node.ne, x + half, y, region, out);
+        self.fill(node.sw, x, y + half, region, out);
+        self.fill(node.se, x + half, y + half, region, out);
+    }
+
+    fn set_in(&mut self, id: NodeId, x: u64, y: u64, alive: bool) -> NodeId {
+        let node = self.node(id);
+        if node.level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1u64 << (node.level - 1);
+        let mut quads = [node.nw, node.ne, node.sw, node.se];
+        let q = (y >= half) as usize * 2 + (x >= half) as usize;
+        quads[q] = self.set_in(quads[q], x % half, y % half, alive);
+        self.join(quads[0], quads[1], quads[2], quads[3])
+    }
+
+    pub(crate) fn get_in(&self, id: NodeId, x: u64, y: u64) -> bool {
+        let node = self.node(id);
+        if node.population == 0 {
+            return false;
+        }
+        if node.level == 0 {
+            return id == ALIVE;
+        }
+        let half = 1u64 << (node.level - 1);
+        let quads = [node.nw, node.ne, node.sw, node.se];
+        let q = (y >= half) as usize * 2 + (x >= half) as usize;
+        self.get_in(quads[q], x % half, y % half)
+    }
+
+    /// Offset of the first live row (or column, if `columns`) within a node,
+    /// or of the last one if `from_end`.
+    fn edge(&self, id: NodeId, columns: bool, from_end: bool, memo: &mut HashMap<NodeId, u64>) -> Option<u64> {
+        let node = self.node(id);
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some(0);
+        }
+        if let Some(&offset) = memo.get(&id) {
+            return Some(offset);
+        }
+        let half = 1u64 << (node.level - 1);
+        let (near, far) = if columns {
+            ([node.nw, node.sw], [node.ne, node.se])
+        } else {
+            ([node.nw, node.ne], [node.sw, node.se])
+        };
+        let mut band = |quads: [NodeId; 2], offset: u64| {
+            let edges = quads.iter().filter_map(|&q| self.edge(q, columns, from_end, memo));
+            if from_end { edges.max() } else { edges.min() }.map(|e| e + offset)
+        };
+        let offset = if from_end {
+            band(far, half).or_else(|| band(near, 0))
+        } else {
+            band(near, 0).or_else(|| band(far, half))
+        }
+        .unwrap();
+        memo.insert(id, offset);
+        Some(offset)
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        let size = 1i64 << self.level();
+        (self.origin_x..self.origin_x + size).contains(&x) && (self.origin_y..self.origin_y + size).contains(&y)
+    }
+
+    /// Wrap the root in a node twice its size, keeping it centred.
+    fn expand(&mut self) {
+        let root = self.node(self.root);
+        let e = self.empty_node(root.level - 1);
+        let nw = self.join(e, e, e, root.nw);
+        let ne = self.join(e, e, root.ne, e);
+        let sw = self.join(e, root.sw, e, e);
+        let se = self.join(root.se, e, e, e);
+        self.root = self.join(nw, ne, sw, se);
+        let shift = 1i64 << (root.level - 1);
+        self.origin_x -= shift;
+        self.origin_y -= shift;
+    }
+
+    /// Whether all live cells lie in the central quarter of the root, so that
+    /// nothing can escape the region returned by `successor`.
+    fn is_padded(&self) -> bool {
+        let root = self.node(self.root);
+        if root.level < 3 {
+            return false;
+        }
+        let population = |id| self.node(id).population;
+        let (nw, ne, sw, se) = (self.node(root.nw), self.node(root.ne), self.node(root.sw), self.node(root.se));
+        nw.population == population(self.node(nw.se).se)
+            && ne.population == population(self.node(ne.sw).sw)
+            && sw.population == population(self.node(sw.ne).ne)
+            && se.population == population(self.node(se.nw).nw)
+    }
+
+    /// One generation of the centre 2x2 of a level 2 node.
+    fn life_4x4(&mut self, id: NodeId) -> NodeId {
+        let node = self.node(id);
+        let mut grid = [[false; 4]; 4];
+        for (q, &quad) in [node.nw, node.ne, node.sw, node.se].iter().enumerate() {
+            let quad = self.node(quad);
+            for (s, &leaf) in [quad.nw, quad.ne, quad.sw, quad.se].iter().enumerate() {
+                grid[(q / 2) * 2 + s / 2][(q % 2) * 2 + s % 2] = leaf == ALIVE;
+            }
+        }
+        let mut next = [DEAD; 4];
+        for (i, (row, col)) in [(1, 1), (1, 2), (2, 1), (2, 2)].iter().cloned().enumerate() {
+            let count = grid[row - 1..=row + 1]
+                .iter()
+                .flat_map(|line| &line[col - 1..=col + 1])
+                .filter(|&&alive| alive)
+                .count() as u32
+                - grid[row][col] as u32;
+            let cell = if grid[row][col] { Cell::Alive } else { Cell::Dead };
+            if self.rule.next(cell, count) == Cell::Alive {
+                next[i] = ALIVE;
+            }
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    /// The centre half of a level `k` node, advanced `2^j` generations, with
+    /// `j` clamped to `k - 2`.
+    fn successor(&mut self, id: NodeId, j: u8) -> NodeId {
+        let m = self.node(id);
+        if m.population == 0 {
+            return self.empty_node(m.level - 1);
+        }
+        if m.level == 2 {
+            return self.life_4x4(id);
+        }
+        let j = j.min(m.level - 2);
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+
+        let (a, b, c, d) = (self.node(m.nw), self.node(m.ne), self.node(m.sw), self.node(m.se));
+        let n1 = self.join(a.ne, b.nw, a.se, b.sw);
+        let n2 = self.join(a.sw, a.se, c.nw, c.ne);
+        let n3 = self.join(a.se, b.sw, c.ne, d.nw);
+        let n4 = self.join(b.sw, b.se, d.nw, d.ne);
+        let n5 = self.join(c.ne, d.nw, c.se, d.sw);
+        let c1 = self.successor(m.nw, j);
+        let c2 = self.successor(n1, j);
+        let c3 = self.successor(m.ne, j);
+        let c4 = self.successor(n2, j);
+        let c5 = self.successor(n3, j);
+        let c6 = self.successor(n4, j);
+        let c7 = self.successor(m.sw, j);
+        let c8 = self.successor(n5, j);
+        let c9 = self.successor(m.se, j);
+
+        let result = if j < m.level - 2 {
+            let [c1, c2, c3, c4, c5, c6, c7, c8, c9] = [c1, c2, c3, c4, c5, c6, c7, c8, c9].map(|id| self.node(id));
+            let nw = self.join(c1.se, c2.sw, c4.ne, c5.nw);
+            let ne = self.join(c2.se, c3.sw, c5.ne, c6.nw);
+            let sw = self.join(c4.se, c5.sw, c7.ne, c8.nw);
+            let se = self.join(c5.se, c6.sw, c8.ne, c9.nw);
+            self.join(nw, ne, sw, se)
+        } else {
+            let (nw, ne, sw, se) = (
+                self.join(c1, c2, c4, c5),
+                self.join(c2, c3, c5, c6),
+                self.join(c4, c5, c7, c8),
+                self.join(c5, c6, c8, c9),
+            );
+            let nw = self.successor(nw, j);
+            let ne = self.successor(ne, j);
+            let sw = self.successor(sw, j);
+            let se = self.successor(se, j);
+            self.join(nw, ne, sw, se)
+        };
+        self.results.insert((id, j), result);
+        result
+    }
+
+    /// Rebuild the arena keeping only the nodes reachable from the root.
+    fn collect_garbage(&mut self) {
+        let mut fresh = HashLife::with_rule(self.rule.clone()).unwrap();
+        let mut copied = HashMap::new();
+        fresh.root = fresh.copy_from(self, self.root, &mut copied);
+        self.nodes = fresh.nodes;
+        self.index = fresh.index;
+        self.empty = fresh.empty;
+        self.results = fresh.results;
+        self.root = fresh.root;
+    }
+
+    fn copy_from(&mut self, other: &HashLife, id: NodeId, copied: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        if id == DEAD || id == ALIVE {
+            return id;
+        }
+        if let Some(&new) = copied.get(&id) {
+            return new;
+        }
+        let node = other.node(id);
+        let nw = self.copy_from(other, node.nw, copied);
+        let ne = self.copy_from(other, node.ne, copied);
+        let sw = self.copy_from(other, node.sw, copied);
+        let se = self.copy_from(other, node.se, copied);
+        let new = self.join(nw, ne, sw, se);
+        copied.insert(id, new);
+        new
+    }
+}
+
+struct Region {
+    x: i64,
+    y: i64,
+    width: u32,
+    height: u32,
+}
+
+impl Region {
+    fn overlaps(&self, x: i64, y: i64, size: i64) -> bool {
+        x < self.x + self.width as i64 && self.x < x + size && y < self.y + self.height as i64 && self.y < y + size
+    }
+}
+
+#[wasm_bindgen]
+impl HashLife {
+    /// An empty plane running Conway's Game of Life.
+    pub fn new() -> HashLife {
+        HashLife::with_rule(Rule::default()).unwrap()
+    }
+
+    /// Load the cells and rule of `universe`, with its top-left cell at
+    /// `(0, 0)`. The torus wrapping of `universe` is not carried over.
+    pub fn from_universe(universe: &Universe) -> Result<HashLife, JsValue> {
+        Ok(HashLife::from_cells(universe.width, universe.height, &universe.cells, universe.rule.clone())?)
+    }
+
+    /// Flatten the `width x height` region with top-left corner `(x, y)` into
+    /// a new `Universe` running the same rule.
+    pub fn to_universe(&self, x: i64, y: i64, width: u32, height: u32) -> Universe {
+        let mut universe = Universe::with_size(width, height);
+        universe.cells = self.get_cells(x, y, width, height);
+        universe.rule = self.rule.clone();
+        universe
+    }
+
+    pub fn is_alive(&self, x: i64, y: i64) -> bool {
+        self.contains(x, y) && self.get_in(self.root, (x - self.origin_x) as u64, (y - self.origin_y) as u64)
+    }
+
+    /// Set the cell at `(x, y)`, growing the tree to reach it. Fails
+    /// without changing anything if the cell lies further out than the
+    /// deepest tree `step` allows.
+    pub fn set_cell(&mut self, x: i64, y: i64, alive: bool) -> Result<(), StepError> {
+        while !self.contains(x, y) {
+            if self.level() >= MAX_LEVEL {
+                return Err(StepError::TooLarge);
+            }
+            self.expand();
+        }
+        let (rx, ry) = ((x - self.origin_x) as u64, (y - self.origin_y) as u64);
+        self.root = self.set_in(self.root, rx, ry, alive);
+        Ok(())
+    }
+
+    /// Advance the pattern by `2^k` generations. Fails without changing
+    /// the pattern if `k` is above `MAX_STEP`, the pattern spreads too far
+    /// for 64-bit coordinates or the generation count would overflow.
+    pub fn step(&mut self, k: u8) -> Result<(), StepError> {
+        if k > MAX_STEP {
+            return Err(StepError::TooLarge);
+        }
+        let generation = self.generation.checked_add(1 << k).ok_or(StepError::GenerationOverflow)?;
+        if self.nodes.len() > GC_THRESHOLD {
+            self.collect_garbage();
+        }
+        while !self.is_padded() || self.level() < k + 2 {
+            if self.level() >= MAX_LEVEL - 1 {
+                return Err(StepError::TooLarge);
+            }
+            self.expand();
+        }
+        self.expand();
+        let shift = 1i64 << (self.level() - 2);
+        self.root = self.successor(self.root, k);
+        self.origin_x += shift;
+        self.origin_y += shift;
+        self.generation = generation;
+        Ok(())
+    }
+
+    /// Advance the pattern by `generations`, one power-of-two step per set
+    /// bit. Fails up front if the count is out of range; a pattern that
+    /// spreads too far stops at the last step that fitted.
+    pub fn advance(&mut self, generations: u64) -> Result<(), StepError> {
+        if generations >> (MAX_STEP + 1) != 0 {
+            return Err(StepError::TooLarge);
+        }
+        self.generation.checked_add(generations).ok_or(StepError::GenerationOverflow)?;
+        for k in 0..=MAX_STEP {
+            if generations >> k & 1 == 1 {
+                self.step(k)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The smallest rectangle containing every live cell, as
+    /// `[x, y, width, height]`, or `undefined` if the plane is empty.
+    pub fn bounds(&self) -> Option<Vec<i64>> {
+        let edge = |columns, from_end| self.edge(self.root, columns, from_end, &mut HashMap::new());
+        let (top, bottom) = (edge(false, false)?, edge(false, true)?);
+        let (left, right) = (edge(true, false)?, edge(true, true)?);
+        Some(vec![
+            self.origin_x + left as i64,
+            self.origin_y + top as i64,
+            (right - left + 1) as i64,
+            (bottom - top + 1) as i64,
+        ])
+    }
+
+    /// Read a pattern in Golly's macrocell format. The root node is centred
+    /// on the origin.
+    pub fn from_macrocell(text: &str) -> Result<HashLife, JsValue> {
+        Ok(crate::formats::macrocell::parse(text)?)
+    }
+
+    pub fn to_macrocell(&self) -> String {
+        crate::formats::macrocell::write(self)
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.node(self.root).population
+    }
+
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+}
+
+impl Default for HashLife {
+    fn default() -> HashLife {
+        HashLife::new()
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Rule, RuleError, Universe};
+
+/// Side length of a tile, in cells.
+const TILE: i32 = 32;
+const TILE_AREA: usize = (TILE * TILE) as usize;
+/// Side length of a tile plus a one-cell border on each side.
+const PADDED: usize = TILE as usize + 2;
+
+type TileKey = (i32, i32);
+
+/// Tile coordinates whose cells all fit in `i32`. Tiles outside would hold
+/// cells the API cannot address, so the plane ends at their edge.
+const TILE_RANGE: std::ops::RangeInclusive<i32> = i32::MIN / TILE..=i32::MAX / TILE;
+
+/// An unbounded plane stored as 32x32 tiles keyed by tile coordinate. Only
+/// tiles holding at least one live cell are kept, so the universe grows and
+/// shrinks with the pattern.
+///
+/// Coordinates are signed, with `x` growing rightwards (a `Universe` column)
+/// and `y` downwards (a row).
+#[wasm_bindgen]
+pub struct InfiniteUniverse {
+    tiles: HashMap<TileKey, Vec<Cell>>,
+    rule: Rule,
+    generation: u64,
+}
+
+fn split(x: i32, y: i32) -> (TileKey, usize) {
+    let key = (x.div_euclid(TILE), y.div_euclid(TILE));
+    let local = (y.rem_euclid(TILE) * TILE + x.rem_euclid(TILE)) as usize;
+    (key, local)
+}
+
+impl InfiniteUniverse {
+    /// An empty plane running `rule`. Generations and non-totalistic rules
+    /// are rejected, as are rules with `B0` since an infinite dead
+    /// background cannot stay sparse under them.
+    pub fn with_rule(rule: Rule) -> Result<InfiniteUniverse, RuleError> {
+        let rule = rule.require_life_like()?;
+        if rule.birth()[0] {
+            return Err(RuleError::Unsupported(rule.to_string()));
+        }
+        Ok(InfiniteUniverse {
+            tiles: HashMap::new(),
+            rule,
+            generation: 0,
+        })
+    }
+
+    /// Set cells to be alive by passing the `(x, y)` of each cell.
+    pub fn set_cells(&mut self, cells: &[(i32, i32)]) {
+        for (x, y) in cells.iter().cloned() {
+            self.set_cell(x, y, true);
+        }
+    }
+
+    /// The `width x height` rectangle with top-left corner `(x, y)`, flattened
+    /// row by row like `Universe::get_cells`.
+    pub fn get_cells(&self, x: i32, y: i32, width: u32, height: u32) -> Vec<Cell> {
+        let mut cells = vec![Cell::Dead; width as usize * height as usize];
+        // In `i64` so that rectangles reaching past `i32::MAX` do not overflow.
+        let (x, y, tile_size) = (x as i64, y as i64, TILE as i64);
+        let (x_end, y_end) = (x + width as i64, y + height as i64);
+        for (&(tx, ty), tile) in self.tiles.iter() {
+            let (left, top) = (tx as i64 * tile_size, ty as i64 * tile_size);
+            for cy in top.max(y)..(top + tile_size).min(y_end) {
+                for cx in left.max(x)..(left + tile_size).min(x_end) {
+                    let cell = tile[((cy - top) * tile_size + (cx - left)) as usize];
+                    cells[(cy - y) as usize * width as usize + (cx - x) as usize] = cell;
+                }
+            }
+        }
+        cells
+    }
+
+    /// Copy the tile at `key` and a one-cell border from its neighbours into
+    /// a `PADDED x PADDED` buffer. Returns false if the buffer is all dead.
+    fn gather(&self, key: TileKey, buffer: &mut [Cell; PADDED * PADDED]) -> bool {
+        buffer.iter_mut().for_each(|c| *c = Cell::Dead);
+        let mut any_alive = false;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let tile = match self.tiles.get(&(key.0 + dx, key.1 + dy)) {
+                    Some(tile) => tile,
+                    None => continue,
+                };
+                // Padded position `p` maps to local coordinate `p - 1 - d * TILE`
+                // in the neighbouring tile.
+                let range = |d: i32| {
+                    (0..PADDED as i32).filter(move |p| (0..TILE).contains(&(p - 1 - d * TILE)))
+                };
+                for py in range(dy) {
+                    for px in range(dx) {
+                        let local = ((py - 1 - dy * TILE) * TILE + (px - 1 - dx * TILE)) as usize;
+                        let cell = tile[local];
+                        buffer[py as usize * PADDED + px as usize] = cell;
+                        any_alive |= cell == Cell::Alive;
+                    }
+                }
+            }
+        }
+        any_alive
+    }
+}
+
+impl From<&Universe> for InfiniteUniverse {
+    /// Copies the live cells of `universe` with its top-left cell at `(0, 0)`.
+    /// A rule this type cannot run is replaced by Conway's Game of Life.
+    fn from(universe: &Universe) -> InfiniteUniverse {
+        let mut infinite = InfiniteUniverse::with_rule(universe.rule.clone()).unwrap_or_default();
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                if universe.cells[universe.get_index(row, col)] == Cell::Alive {
+                    infinite.set_cell(col as i32, row as i32, true);
+                }
+            }
+        }
+        infinite
+    }
+}
+
+#[wasm_bindgen]
+impl InfiniteUniverse {
+    /// An empty plane running Conway's Game of Life.
+    pub fn new() -> InfiniteUniverse {
+        InfiniteUniverse::with_rule(Rule::default()).unwrap()
+    }
+
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        *self = InfiniteUniverse {
+            tiles: std::mem::take(&mut self.tiles),
+            generation: self.generation,
+            ..InfiniteUniverse::with_rule(rule.parse::<Rule>()?)?
+        };
+        Ok(())
+    }
+
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    pub fn is_alive(&self, x: i32, y: i32) -> bool {
+        let (key, local) = split(x, y);
+        self.tiles.get(&key).is_some_and(|tile| tile[local] == Cell::Alive)
+    }
+
+    pub fn set_cell(&mut self, x: i32, y: i32, alive: bool) {
+        let (key, local) = split(x, y);
+        if alive {
+            self.tiles.entry(key).or_insert_with(|| vec![Cell::Dead; TILE_AREA])[local] = Cell::Alive;
+        } else if let Some(tile) = self.tiles.get_mut(&key) {
+            tile[local] = Cell::Dead;
+            if tile.iter().all(|&c| c == Cell::Dead) {
+                self.tiles.remove(&key);
+            }
+        }
+    }
+
+    pub fn toggle_cell(&mut self, x: i32, y: i32) {
+        let alive = self.is_alive(x, y);
+        self.set_cell(x, y, !alive);
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    pub fn tick(&mut self) {
+        let _timer = crate::Timer::new("InfiniteUniverse::tick");
+
+        // Any tile next to a live tile may come alive this generation.
+        let mut candidates = HashSet::new();
+        for &(tx, ty) in self.tiles.keys() {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    candidates.insert((tx + dx, ty + dy));
+                }
+            }
+        }
+
+        let mut buffer = [Cell::Dead; PADDED * PADDED];
+        let mut future = HashMap::with_capacity(self.tiles.len());
+        for key in candidates {
+            if !TILE_RANGE.contains(&key.0) || !TILE_RANGE.contains(&key.1) || !self.gather(key, &mut buffer) {
+                continue;
+            }
+            let mut tile = vec![Cell::Dead; TILE_AREA];
+            let mut any_alive = false;
+            for row in 1..=TILE as usize {
+                for col in 1..=TILE as usize {
+                    let mut count = 0;
+                    for r in row - 1..=row + 1 {
+                        count += buffer[r * PADDED + col - 1..=r * PADDED + col + 1]
+                            .iter()
+                            .filter(|&&c| c == Cell::Alive)
+                            .count() as u32;
+                    }
+                    let cell = buffer[row * PADDED + col];
+                    count -= cell as u32;
+                    let next = self.rule.next(cell, count);
+                    any_alive |= next == Cell::Alive;
+                    tile[(row - 1) * TILE as usize + col - 1] = next;
+                }
+            }
+            if any_alive {
+                future.insert(key, tile);
+            }
+        }
+        self.tiles = future;
+        self.generation += 1;
+    }
+
+    /// The cells inside the `width x height` rectangle with top-left corner
+    /// `(x, y)`, as `Cell` values row by row. Reading this from JS gives a
+    /// `Uint8Array` that can be drawn the same way as `Universe::cells()`.
+    pub fn viewport(&self, x: i32, y: i32, width: u32, height: u32) -> Vec<u8> {
+        self.get_cells(x, y, width, height).into_iter().map(|c| c as u8).collect()
+    }
+
+    /// The smallest rectangle containing every live cell, as
+    /// `[x, y, width, height]`, or `undefined` if the universe is empty.
+    /// Worked out in `i64`, since the width and height can exceed `i32`.
+    pub fn bounds(&self) -> Option<Vec<i64>> {
+        let tile_size = TILE as i64;
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (i64::MAX, i64::MAX, i64::MIN, i64::MIN);
+        for (&(tx, ty), tile) in self.tiles.iter() {
+            for (i, &cell) in tile.iter().enumerate() {
+                if cell == Cell::Alive {
+                    let i = i as i64;
+                    let (x, y) = (tx as i64 * tile_size + i % tile_size, ty as i64 * tile_size + i / tile_size);
+                    min_x = min_x.min(x);
+                    min_y = min_y.min(y);
+                    max_x = max_x.max(x);
+                    max_y = max_y.max(y);
+                }
+            }
+        }
+        if min_x > max_x {
+            return None;
+        }
+        Some(vec![min_x, min_y, max_x - min_x + 1, max_y - min_y + 1])
+    }
+
+    pub fn population(&self) -> u64 {
+        self.tiles
+            .values()
+            .map(|tile| tile.iter().filter(|&&c| c == Cell::Alive).count() as u64)
+            .sum()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Number of 32x32 tiles currently allocated.
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+}
+
+impl Default for InfiniteUniverse {
+    fn default() -> InfiniteUniverse {
+        InfiniteUniverse::new()
+    }
+}
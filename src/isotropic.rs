@@ -0,0 +1,137 @@
+//! Hensel notation for isotropic non-totalistic rules.
+//!
+//! The eight neighbours of a cell are packed into a byte, row by row and
+//! skipping the cell itself: bit 0 is the north-west neighbour, bit 1 north,
+//! bit 2 north-east, bit 3 west, bit 4 east, bit 5 south-west, bit 6 south
+//! and bit 7 south-east. A `Configs` is the set of those bytes a rule
+//! accepts, for either birth or survival.
+
+/// Letters in the order Hensel notation lists them.
+pub(crate) const LETTERS: &str = "cekainyqjrtwz";
+
+/// One representative of every letter for 1 to 4 live neighbours, as a 3x3
+/// row-major mask whose bit 4 is the centre cell. These are the masks Golly
+/// uses; counts 5 to 7 are the complements of counts 3 to 1.
+const CANONICAL: [&[(char, u16)]; 5] = [
+    &[],
+    &[('c', 1), ('e', 2)],
+    &[('c', 5), ('e', 10), ('k', 33), ('a', 3), ('i', 40), ('n', 68)],
+    &[
+        ('c', 69), ('e', 42), ('k', 98), ('a', 11), ('i', 7),
+        ('n', 13), ('y', 97), ('q', 70), ('j', 14), ('r', 41),
+    ],
+    &[
+        ('c', 325), ('e', 170), ('k', 99), ('a', 15), ('i', 45),
+        ('n', 71), ('y', 101), ('q', 102), ('j', 106), ('r', 43),
+        ('t', 105), ('w', 78), ('z', 108),
+    ],
+];
+
+/// Row and column of each neighbour bit.
+const POSITIONS: [(u8, u8); 8] = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)];
+
+/// A set of neighbour configurations.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub(crate) struct Configs([u64; 4]);
+
+impl Configs {
+    /// Every configuration with exactly `count` live neighbours.
+    pub(crate) fn all(count: u8) -> Configs {
+        let mut configs = Configs::default();
+        for neighbours in 0..=255u8 {
+            if neighbours.count_ones() == count as u32 {
+                configs.insert(neighbours);
+            }
+        }
+        configs
+    }
+
+    /// The configurations `letter` stands for with `count` live neighbours,
+    /// or `None` if Hensel notation has no such letter.
+    pub(crate) fn letter(count: u8, letter: char) -> Option<Configs> {
+        if count > 4 {
+            return Configs::letter(8 - count, letter).map(|configs| {
+                let mut complement = Configs::default();
+                for neighbours in configs.iter() {
+                    complement.insert(!neighbours);
+                }
+                complement
+            });
+        }
+        let &(_, mask) = CANONICAL[count as usize].iter().find(|&&(l, _)| l == letter)?;
+        let mut configs = Configs::default();
+        for neighbours in symmetries(from_mask(mask)).iter() {
+            configs.insert(*neighbours);
+        }
+        Some(configs)
+    }
+
+    /// The letters valid for `count` live neighbours, in notation order.
+    pub(crate) fn letters(count: u8) -> impl Iterator<Item = char> {
+        let n = CANONICAL[count.min(8 - count) as usize].len();
+        LETTERS.chars().take(n)
+    }
+
+    pub(crate) fn insert(&mut self, neighbours: u8) {
+        self.0[neighbours as usize / 64] |= 1 << (neighbours % 64);
+    }
+
+    pub(crate) fn contains(&self, neighbours: u8) -> bool {
+        (self.0[neighbours as usize / 64] >> (neighbours % 64)) & 1 == 1
+    }
+
+    pub(crate) fn union(self, other: Configs) -> Configs {
+        let mut words = self.0;
+        words.iter_mut().zip(other.0.iter()).for_each(|(w, o)| *w |= o);
+        Configs(words)
+    }
+
+    pub(crate) fn intersection(self, other: Configs) -> Configs {
+        let mut words = self.0;
+        words.iter_mut().zip(other.0.iter()).for_each(|(w, o)| *w &= o);
+        Configs(words)
+    }
+
+    pub(crate) fn difference(self, other: Configs) -> Configs {
+        let mut words = self.0;
+        words.iter_mut().zip(other.0.iter()).for_each(|(w, o)| *w &= !o);
+        Configs(words)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    fn iter(self) -> impl Iterator<Item = u8> {
+        (0..=255u8).filter(move |&n| self.contains(n))
+    }
+}
+
+/// Drop the centre bit from a 3x3 mask.
+fn from_mask(mask: u16) -> u8 {
+    ((mask & 0xf) | ((mask >> 5) << 4)) as u8
+}
+
+/// The configuration under each of the eight rotations and reflections.
+fn symmetries(neighbours: u8) -> [u8; 8] {
+    let transform = |f: &dyn Fn(u8, u8) -> (u8, u8)| {
+        let mut out = 0;
+        for (bit, &(row, col)) in POSITIONS.iter().enumerate() {
+            if (neighbours >> bit) & 1 == 1 {
+                let target = f(row, col);
+                out |= 1 << POSITIONS.iter().position(|&p| p == target).unwrap();
+            }
+        }
+        out
+    };
+    [
+        transform(&|r, c| (r, c)),
+        transform(&|r, c| (c, 2 - r)),
+        transform(&|r, c| (2 - r, 2 - c)),
+        transform(&|r, c| (2 - c, r)),
+        transform(&|r, c| (r, 2 - c)),
+        transform(&|r, c| (2 - r, c)),
+        transform(&|r, c| (c, r)),
+        transform(&|r, c| (2 - c, 2 - r)),
+    ]
+}
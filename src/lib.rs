@@ -1,28 +1,73 @@
 mod utils;
+mod rule;
+mod isotropic;
+mod neighborhood;
+mod ltl;
+mod wireworld;
+mod turmite;
+mod margolus;
+mod stochastic;
+mod delta;
+mod tiles;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "rayon")]
+mod threads;
+mod packed;
+mod hashlife;
+mod infinite;
+mod topology;
+mod rng;
+pub mod formats;
+
+pub use rule::{Counts, Rule, RuleError};
+pub use neighborhood::Neighborhood;
+pub use wireworld::WireCell;
+pub use turmite::{Ant, AntRule, Heading, Turn};
+pub use margolus::MargolusRule;
+pub use tiles::TILE_SIZE;
+use tiles::Band;
+#[cfg(all(feature = "threads", target_arch = "wasm32"))]
+pub use threads::{init_thread_pool, thread_pool_ready};
+pub use packed::PackedUniverse;
+pub use hashlife::{HashLife, StepError, MAX_STEP};
+pub use infinite::InfiniteUniverse;
+pub use topology::{CoordinateError, Topology};
+pub use rng::Rng;
+pub use formats::{Pattern, PatternError};
 
 use wasm_bindgen::prelude::*;
 use std::fmt;
-use js_sys;
 extern crate web_sys;
 use web_sys::console;
 
+use stochastic::clamp_probability;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
 pub struct Timer<'a> {
     name: &'a str,
 }
 
 impl<'a> Timer<'a> {
     pub fn new(name: &'a str) -> Timer<'a>{
-        console::time_with_label(name);
+        // `console` is only reachable inside a JS host.
+        if cfg!(target_arch = "wasm32") {
+            console::time_with_label(name);
+        }
         Timer { name }
     }
 }
 
 impl<'a> Drop for Timer<'a> {
     fn drop(&mut self) {
-        console::time_end_with_label(self.name);
+        if cfg!(target_arch = "wasm32") {
+            console::time_end_with_label(self.name);
+        }
     }
 }
 
+#[allow(unused_macros)]
 macro_rules! log {
     ( $( $t:tt )* ) => {
         console::log_1(&format!( $( $t )* ).into());
@@ -43,6 +88,12 @@ pub enum Cell {
     Alive = 1,
 }
 
+impl From<Cell> for u8 {
+    fn from(cell: Cell) -> u8 {
+        cell as u8
+    }
+}
+
 impl Cell {
     fn toggle(&mut self) {
         *self = match *self {
@@ -52,11 +103,102 @@ impl Cell {
     }
 }
 
+/// The part of the board that stays in place when a `Universe` is resized.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+/// What drives `Universe::tick`.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// The rule set with `Universe::set_rule`.
+    #[default]
+    Life = 0,
+    /// The WireWorld automaton, with each cell a `WireCell`.
+    WireWorld = 1,
+    /// Ants walking over coloured cells, following an `AntRule`.
+    Turmite = 2,
+    /// 2x2 blocks replaced through a `MargolusRule`.
+    Margolus = 3,
+}
+
+impl Anchor {
+    /// How far existing cells move, as `(rows, columns)`, when a board is
+    /// resized from `width x height` to `new_width x new_height`.
+    fn offset(self, width: u32, height: u32, new_width: u32, new_height: u32) -> (i64, i64) {
+        let d_width = new_width as i64 - width as i64;
+        let d_height = new_height as i64 - height as i64;
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::TopRight => (0, d_width),
+            Anchor::BottomLeft => (d_height, 0),
+            Anchor::BottomRight => (d_height, d_width),
+            Anchor::Center => (d_height.div_euclid(2), d_width.div_euclid(2)),
+        }
+    }
+}
+
 #[wasm_bindgen]
 pub struct Universe {
     width: u32,
     height: u32,
     cells: Vec<Cell>,
+    /// One byte per cell for Generations rules (0 dead, 1 alive and 2 or
+    /// more dying), in WireWorld mode (a `WireCell`) and in turmite mode
+    /// (the cell colour). Kept in step with `cells`, where state 1 is
+    /// alive, and empty for two-state rules.
+    states: Vec<u8>,
+    mode: Mode,
+    rule: Rule,
+    /// The previous generation's buffers, reused by `tick` to build the
+    /// next one instead of allocating a new board every generation. Their
+    /// contents are meaningless between ticks.
+    next_cells: Vec<Cell>,
+    next_states: Vec<u8>,
+    /// Indices whose `cells()` byte changed since `reset_changed`, each
+    /// listed once thanks to `changed_mark`.
+    changed: Vec<u32>,
+    changed_mark: Vec<bool>,
+    /// Per `TILE_SIZE` tile, row by row, whether any of its cells changed in
+    /// the last generation or was edited since. Empty when unknown, which
+    /// makes the next generation compute every tile.
+    tile_activity: Vec<bool>,
+    /// Tiles the last generation left alone because nothing near them
+    /// changed.
+    skipped_tiles: u32,
+    ant_rule: AntRule,
+    ants: Vec<Ant>,
+    margolus_rule: MargolusRule,
+    /// Which Margolus block grid the next `tick` uses, 0 or 1.
+    phase: u8,
+    /// Chance that a birth or survival called for by the rule happens, and
+    /// that any cell flips afterwards, drawn from `rng`.
+    birth_probability: f64,
+    survival_probability: f64,
+    noise: f64,
+    rng: Rng,
+    topology: Topology,
+    /// Column offset applied when wrapping vertically on a twisted torus.
+    shift: u32,
+}
+
+/// Number of cells on a `width` by `height` board.
+///
+/// Panics if the board has more cells than a `u32` can index, which the rest
+/// of the crate relies on.
+pub(crate) fn cell_count(width: u32, height: u32) -> usize {
+    (width as usize)
+        .checked_mul(height as usize)
+        .filter(|&count| count <= u32::MAX as usize)
+        .unwrap_or_else(|| panic!("a {}x{} universe has too many cells", width, height))
 }
 
 impl Universe {
@@ -64,44 +206,291 @@ impl Universe {
         (row * self.width + col) as usize
     }
 
+    /// Index of a possibly out-of-range cell after applying the topology,
+    /// or `None` if it lies beyond a dead edge.
+    fn resolve(&self, row: i64, col: i64) -> Option<usize> {
+        self.topology
+            .resolve(row, col, self.width, self.height, self.shift)
+            .map(|(row, col)| self.get_index(row, col))
+    }
+
     fn neigh_alive_count(&self, row: u32, col: u32) -> u8 {
         let mut count = 0;
-        for d_row in [self.height -1, 0, 1].iter().cloned() {
-            for d_col in [self.width -1, 0, 1].iter().cloned() {
+        let interior = row > 0 && col > 0 && row + 1 < self.height && col + 1 < self.width;
+        for d_row in [-1, 0, 1].iter().cloned() {
+            for d_col in [-1, 0, 1].iter().cloned() {
                 if d_row == 0 && d_col == 0 {
                     continue;
                 }
 
-                let n_row = (row + d_row) % self.height;
-                let n_col = (col + d_col) % self.width;
-                let i = self.get_index(n_row, n_col);
+                let (n_row, n_col) = (row as i64 + d_row, col as i64 + d_col);
+                let i = if interior {
+                    self.get_index(n_row as u32, n_col as u32)
+                } else {
+                    match self.resolve(n_row, n_col) {
+                        Some(i) => i,
+                        None => continue,
+                    }
+                };
                 count += self.cells[i] as u8;
             }
         }
         count
     }
 
+    /// Live cells at `offsets` from a cell, for neighbourhoods other than
+    /// the 3x3 Moore one.
+    fn neigh_count(&self, row: u32, col: u32, offsets: &[(i64, i64)]) -> u32 {
+        offsets
+            .iter()
+            .filter_map(|&(d_row, d_col)| self.resolve(row as i64 + d_row, col as i64 + d_col))
+            .map(|i| self.cells[i] as u32)
+            .sum()
+    }
+
+    /// Live neighbours under the rule's neighbourhood, where `offsets` comes
+    /// from `Rule::offsets`.
+    fn alive_count(&self, row: u32, col: u32, offsets: &[(i64, i64)]) -> u32 {
+        if offsets.is_empty() {
+            self.neigh_alive_count(row, col).into()
+        } else {
+            self.neigh_count(row, col, offsets)
+        }
+    }
+
+    /// Which of the eight neighbours are alive, one bit each from north-west
+    /// to south-east row by row, as `Rule::next_configuration` expects.
+    fn neigh_configuration(&self, row: u32, col: u32) -> u8 {
+        let mut neighbours = 0;
+        let mut bit = 0;
+        for d_row in [-1, 0, 1].iter().cloned() {
+            for d_col in [-1, 0, 1].iter().cloned() {
+                if d_row == 0 && d_col == 0 {
+                    continue;
+                }
+
+                if let Some(i) = self.resolve(row as i64 + d_row, col as i64 + d_col) {
+                    neighbours |= (self.cells[i] as u8) << bit;
+                }
+                bit += 1;
+            }
+        }
+        neighbours
+    }
+
+    /// Change the width, killing every cell. Use `resize` to keep the
+    /// current pattern. Panics if the board would be too large.
     pub fn set_width(&mut self, width: u32){
+        self.cells = vec![Cell::Dead; cell_count(width, self.height)];
         self.width = width;
-        self.cells = (0..width * self.height).map(|_i| Cell::Dead).collect();
+        self.replace_rule(self.rule.clone());
+        self.ants.retain(|ant| ant.col < width);
+        self.clear_changed();
     }
 
+    /// Change the height, killing every cell. Use `resize` to keep the
+    /// current pattern. Panics if the board would be too large.
     pub fn set_height(&mut self, height: u32){
+        self.cells = vec![Cell::Dead; cell_count(self.width, height)];
         self.height = height;
-        self.cells = (0..self.width * height).map(|_i| Cell::Dead).collect();
+        self.replace_rule(self.rule.clone());
+        self.ants.retain(|ant| ant.row < height);
+        self.clear_changed();
     }
 
+    /// Live and dead cells. Dying cells of a Generations rule are dead here,
+    /// see `get_states`.
     pub fn get_cells(&self) -> &[Cell] {
         &self.cells
     }
 
+    /// The state byte of every cell: 0 dead, 1 alive and, for Generations
+    /// rules, 2 or more dying.
+    pub fn get_states(&self) -> Vec<u8> {
+        if self.states.is_empty() {
+            self.cells.iter().map(|&cell| cell as u8).collect()
+        } else {
+            self.states.clone()
+        }
+    }
+
+    /// Indices of the cells whose byte in `cells()` changed since the last
+    /// `reset_changed`, see `changed_cells`.
+    pub fn changed(&self) -> &[u32] {
+        &self.changed
+    }
+
+    /// Every ant, in the order they were added.
+    pub fn ants(&self) -> &[Ant] {
+        &self.ants
+    }
+
+    /// Switch to turmite mode with `rule`. Cells with a colour the rule
+    /// does not have wrap around to one it does.
+    pub fn set_ant_rule(&mut self, rule: AntRule) {
+        self.set_mode(Mode::Turmite);
+        let colours = rule.colours();
+        self.ant_rule = rule;
+        for i in 0..self.states.len() {
+            self.write_state(i, self.states[i] % colours);
+        }
+    }
+
+    /// Switch to Margolus mode with `rule`, starting from phase 0.
+    pub fn set_margolus_rule(&mut self, rule: MargolusRule) {
+        self.set_mode(Mode::Margolus);
+        self.margolus_rule = rule;
+        self.phase = 0;
+    }
+
+    /// Set a cell, keeping the Generations states in step.
+    fn write_cell(&mut self, i: usize, cell: Cell) {
+        let before = self.state(i);
+        self.cells[i] = cell;
+        if !self.states.is_empty() {
+            self.states[i] = cell as u8;
+        }
+        if self.state(i) != before {
+            self.mark_changed(i);
+        }
+    }
+
+    /// The byte `cells()` holds for cell `i`.
+    fn state(&self, i: usize) -> u8 {
+        if self.states.is_empty() {
+            self.cells[i] as u8
+        } else {
+            self.states[i]
+        }
+    }
+
+    /// Switch rules, allocating or dropping the Generations states as
+    /// needed. Dying cells become dead when switching to a two-state rule.
+    fn replace_rule(&mut self, rule: Rule) {
+        self.states = if matches!(self.mode, Mode::WireWorld | Mode::Turmite) || rule.states() > 2 {
+            self.cells.iter().map(|&cell| cell as u8).collect()
+        } else {
+            Vec::new()
+        };
+        self.rule = rule;
+        self.invalidate_tiles();
+    }
+
+    /// Set a cell to a state byte, keeping `cells` in step. Without
+    /// per-cell states any state but 0 is alive.
+    fn write_state(&mut self, i: usize, state: u8) {
+        let before = self.state(i);
+        if self.states.is_empty() {
+            self.cells[i] = if state != 0 { Cell::Alive } else { Cell::Dead };
+        } else {
+            self.states[i] = state;
+            self.cells[i] = if state == 1 { Cell::Alive } else { Cell::Dead };
+        }
+        if self.state(i) != before {
+            self.mark_changed(i);
+        }
+    }
+
+    /// Toggle a cell, or in WireWorld mode step it through the wire states.
+    fn toggle_index(&mut self, i: usize) {
+        if self.mode == Mode::WireWorld {
+            let wire = WireCell::from_state(self.states[i]).cycle();
+            self.write_state(i, wire as u8);
+            return;
+        }
+        if self.mode == Mode::Turmite {
+            self.write_state(i, self.ant_rule.next_colour(self.states[i]));
+            return;
+        }
+        let mut cell = self.cells[i];
+        cell.toggle();
+        self.write_cell(i, cell);
+    }
+
+    /// The next state of a two-state cell, given the rule's neighbour
+    /// offsets and any summed-area counts.
+    fn next_cell(&self, row: u32, col: u32, offsets: &[(i64, i64)], sums: &Option<Vec<u32>>) -> Cell {
+        let i = self.get_index(row, col);
+        let cell = self.cells[i];
+
+        if !self.rule.is_totalistic() {
+            return self.rule.next_configuration(cell, self.neigh_configuration(row, col));
+        }
+        let alive_count = match sums {
+            Some(sums) => sums[i],
+            None => self.alive_count(row, col, offsets),
+        };
+
+        // log!(
+        //     "cell [{}, {}] was {:?} and has {} live neighbours",
+        //     row,
+        //     col,
+        //     cell,
+        //     alive_count
+        // );
+
+        self.rule.next(cell, alive_count)
+    }
+
+    /// One generation of a Generations rule. Only fully alive cells count
+    /// as neighbours, which `cells` already reflects.
+    fn tick_generations(&mut self) {
+        let offsets = self.rule.offsets();
+        let sums = self.box_counts();
+        let mut future = self.take_next_states();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let i = self.get_index(row, col);
+                future[i] = if self.rule.is_totalistic() {
+                    let alive_count = match &sums {
+                        Some(sums) => sums[i],
+                        None => self.alive_count(row, col, &offsets),
+                    };
+                    self.rule.next_state(self.states[i], alive_count)
+                } else {
+                    self.rule.next_state_configuration(self.states[i], self.neigh_configuration(row, col))
+                };
+            }
+        }
+        for (cell, &state) in self.cells.iter_mut().zip(future.iter()) {
+            *cell = if state == 1 { Cell::Alive } else { Cell::Dead };
+        }
+        self.next_states = std::mem::replace(&mut self.states, future);
+    }
+
+    /// The spare state buffer, sized to the board, for building the next
+    /// generation in. Every cell must be written before it is swapped in.
+    pub(crate) fn take_next_states(&mut self) -> Vec<u8> {
+        let mut future = std::mem::take(&mut self.next_states);
+        future.resize(self.states.len(), 0);
+        future
+    }
+
+    /// Drop the spare buffers, so the next `tick` allocates a fresh board
+    /// as it did before it kept them. Only there for `benches/tick.rs` to
+    /// compare the two.
+    #[doc(hidden)]
+    pub fn drop_spare_buffers(&mut self) {
+        self.next_cells = Vec::new();
+        self.next_states = Vec::new();
+    }
+
+    fn checked_index(&self, row: i64, col: i64) -> Result<usize, CoordinateError> {
+        topology::checked_index(row, col, self.width, self.height)
+    }
+
     /// Set cells to be alive in a universe by passing the row and column
-    /// of each cell as an array.
-    pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
-        for (row, col) in cells.iter().cloned() {
-            let idx = self.get_index(row, col);
-            self.cells[idx] = Cell::Alive;
+    /// of each cell as an array. Nothing is changed if any cell lies outside
+    /// the universe.
+    pub fn set_cells(&mut self, cells: &[(u32, u32)]) -> Result<(), CoordinateError> {
+        let indices = cells
+            .iter()
+            .map(|&(row, col)| self.checked_index(row as i64, col as i64))
+            .collect::<Result<Vec<_>, _>>()?;
+        for idx in indices {
+            self.write_cell(idx, Cell::Alive);
         }
+        Ok(())
     }
 
 }
@@ -111,58 +500,241 @@ impl Universe {
     pub fn tick(&mut self) {
         let _timer = Timer::new("Universe::tick");
 
-        let mut future = self.cells.clone();
+        if self.mode == Mode::WireWorld {
+            self.tick_wireworld();
+            self.record_swapped();
+            return;
+        }
+        if self.mode == Mode::Turmite {
+            self.tick_turmite();
+            return;
+        }
+        if self.mode == Mode::Margolus {
+            let rule = self.margolus_rule;
+            self.step_blocks(&rule);
+            self.phase ^= 1;
+            return;
+        }
+        // After the swap the spare buffers hold the generation we started
+        // from, which is all the stochastic pass needs to look back at.
+        let stochastic = self.is_stochastic();
+        if !self.states.is_empty() {
+            self.tick_generations();
+            self.record_swapped();
+            if stochastic {
+                let before = std::mem::take(&mut self.next_states);
+                self.apply_chance(&before);
+                self.next_states = before;
+            }
+            return;
+        }
 
-        for row in 0..self.height {
-            for col in 0..self.width {
-                let i = self.get_index(row, col);
-                let cell = self.cells[i];
-                let alive_count = self.neigh_alive_count(row, col);
+        // Skipping tiles relies on each cell depending only on cells within
+        // a tile of it, and on the last generation following the rule.
+        let range = self.rule.range() as u32;
+        if stochastic || range > TILE_SIZE {
+            self.invalidate_tiles();
+        }
+        let dirty = self.dirty_tiles(range);
 
-                // log!(
-                //     "cell [{}, {}] was {:?} and has {} live neighbours",
-                //     row,
-                //     col,
-                //     cell,
-                //     alive_count
-                // );
+        let offsets = self.rule.offsets();
+        let sums = self.box_counts();
+        let mut future = std::mem::take(&mut self.next_cells);
+        future.resize(self.cells.len(), Cell::Dead);
 
-                let future_cell = match (cell, alive_count) {
-                    //Rule 1: Any live cell with < 2 neighbouring live cell dies (Underpopulation)
-                    (Cell::Alive, x) if x < 2 => Cell::Dead,
+        // Each band is one row of tiles. Bands only read the current
+        // generation, so they can be computed in any order or on any thread
+        // and always give the same board.
+        let band_len = (self.width as usize * TILE_SIZE as usize).max(1);
+        let tile_cols = self.tile_grid().1 as usize;
+        let step = |(tile_row, band): (usize, &mut [Cell])| {
+            self.tick_band(tile_row, &dirty[tile_row * tile_cols..][..tile_cols], band, &offsets, &sums)
+        };
+        #[cfg(feature = "rayon")]
+        let bands: Vec<Band> = if threads::use_threads() {
+            future.par_chunks_mut(band_len).enumerate().map(step).collect()
+        } else {
+            future.chunks_mut(band_len).enumerate().map(step).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let bands: Vec<Band> = future.chunks_mut(band_len).enumerate().map(step).collect();
 
-                    //Rule 2: Any live cell with 2 or 3 live cell lives 
-                    (Cell::Alive, 2) | (Cell::Alive, 3) =>  Cell::Alive,
+        let mut activity = Vec::with_capacity(dirty.len());
+        self.skipped_tiles = 0;
+        for band in bands {
+            activity.extend(band.activity);
+            self.skipped_tiles += band.skipped;
+            for i in band.changed {
+                self.mark_changed(i);
+            }
+        }
+        self.next_cells = std::mem::replace(&mut self.cells, future);
+        if stochastic {
+            let before = std::mem::take(&mut self.next_cells);
+            self.apply_chance(&before);
+            self.next_cells = before;
+        } else if range <= TILE_SIZE {
+            self.tile_activity = activity;
+        }
+    }
 
-                    //Rule 3: Any live cell with > 3 neighbouring live cell dies (Oveerpopulation)
-                    (Cell::Alive, x) if x > 3 => Cell::Dead,
+    /// Number of tiles `tick` tracks, see `skipped_tiles`.
+    pub fn tile_count(&self) -> u32 {
+        let (tile_rows, tile_cols) = self.tile_grid();
+        tile_rows * tile_cols
+    }
 
-                    //Rule 4: Any dead cell with exactly 3 live neighbours becomes alive
-                    (Cell::Dead, 3) => Cell::Alive,
+    /// How many of the `TILE_SIZE` by `TILE_SIZE` tiles the last generation
+    /// skipped because no cell in or near them had changed. Only life-like
+    /// rules without chance or noise skip tiles.
+    pub fn skipped_tiles(&self) -> u32 {
+        self.skipped_tiles
+    }
 
-                    (otherwise, _) => otherwise 
-                };
+    /// Step a Margolus universe back one generation. Fails outside
+    /// Margolus mode or if the block table is not reversible.
+    pub fn untick(&mut self) -> Result<(), RuleError> {
+        let inverse = match self.mode {
+            Mode::Margolus => self.margolus_rule.inverse(),
+            _ => None,
+        };
+        let inverse = inverse.ok_or_else(|| RuleError::Unsupported(self.rule()))?;
+        self.phase ^= 1;
+        self.step_blocks(&inverse);
+        Ok(())
+    }
 
-                //log!("  It becomes {:?}", future_cell);
+    /// Chance, clamped to `[0, 1]`, that a cell the rule would bring to
+    /// life is born. Applies to life-like and Generations rules.
+    pub fn set_birth_probability(&mut self, probability: f64) {
+        self.birth_probability = clamp_probability(probability);
+    }
 
-                future[i] = future_cell;
-            }
-        }
-        self.cells = future;
+    pub fn birth_probability(&self) -> f64 {
+        self.birth_probability
+    }
+
+    /// Chance, clamped to `[0, 1]`, that a live cell the rule would keep
+    /// alive survives. Cells that fail start dying under Generations rules.
+    pub fn set_survival_probability(&mut self, probability: f64) {
+        self.survival_probability = clamp_probability(probability);
     }
-    
+
+    pub fn survival_probability(&self) -> f64 {
+        self.survival_probability
+    }
+
+    /// Chance, clamped to `[0, 1]`, that each cell is flipped between
+    /// alive and dead after every generation.
+    pub fn set_noise(&mut self, rate: f64) {
+        self.noise = clamp_probability(rate);
+    }
+
+    pub fn noise(&self) -> f64 {
+        self.noise
+    }
+
+    /// Reseed the generator behind the probabilities and noise, so that a
+    /// noisy run can be repeated exactly.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// Which Margolus block grid the next `tick` uses: 0 for blocks on even
+    /// rows and columns, 1 for odd ones.
+    pub fn phase(&self) -> u8 {
+        self.phase
+    }
+
     pub fn render(&self) -> String {
         self.to_string()
     }
 
-    pub fn new() -> Universe {
+    /// An all-dead universe running Conway's Game of Life. Panics if the
+    /// board has more cells than a `u32` can index.
+    pub fn with_size(width: u32, height: u32) -> Universe {
         utils::set_panic_hook();
-        let width = 64;
-        let height = 64;
+        let count = cell_count(width, height);
+        Universe {
+            width,
+            height,
+            cells: vec![Cell::Dead; count],
+            states: Vec::new(),
+            mode: Mode::Life,
+            rule: Rule::default(),
+            next_cells: Vec::new(),
+            next_states: Vec::new(),
+            changed: Vec::new(),
+            changed_mark: vec![false; count],
+            tile_activity: Vec::new(),
+            skipped_tiles: 0,
+            ant_rule: AntRule::default(),
+            ants: Vec::new(),
+            margolus_rule: MargolusRule::default(),
+            phase: 0,
+            birth_probability: 1.0,
+            survival_probability: 1.0,
+            noise: 0.0,
+            rng: Rng::new(0),
+            topology: Topology::default(),
+            shift: 0,
+        }
+    }
+
+    /// An all-dead 64x64 universe, the same size as `new`.
+    pub fn empty() -> Universe {
+        Universe::with_size(64, 64)
+    }
+
+    /// Change the size of the universe while keeping its pattern. `anchor`
+    /// picks which part of the board stays fixed; cells that no longer fit
+    /// are dropped and new space is dead. Panics if the board would be too
+    /// large.
+    pub fn resize(&mut self, width: u32, height: u32, anchor: Anchor) {
+        let (d_row, d_col) = anchor.offset(self.width, self.height, width, height);
+        let mut cells = vec![Cell::Dead; cell_count(width, height)];
+        let mut states = vec![0; if self.states.is_empty() { 0 } else { cells.len() }];
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let (n_row, n_col) = (row as i64 + d_row, col as i64 + d_col);
+                if (0..height as i64).contains(&n_row) && (0..width as i64).contains(&n_col) {
+                    let (from, to) = (self.get_index(row, col), (n_row as u32 * width + n_col as u32) as usize);
+                    cells[to] = self.cells[from];
+                    if !states.is_empty() {
+                        states[to] = self.states[from];
+                    }
+                }
+            }
+        }
+        self.ants.retain_mut(|ant| {
+            let (n_row, n_col) = (ant.row as i64 + d_row, ant.col as i64 + d_col);
+            ant.row = n_row as u32;
+            ant.col = n_col as u32;
+            (0..height as i64).contains(&n_row) && (0..width as i64).contains(&n_col)
+        });
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+        self.states = states;
+        self.clear_changed();
+        self.invalidate_tiles();
+    }
+
+    /// A 64x64 universe with each cell alive at random, seeded from the
+    /// browser's `Math.random()` (or the clock on native targets).
+    pub fn new() -> Universe {
+        Universe::random(64, 64, 0.5, Rng::from_entropy().next_u64())
+    }
+
+    /// A universe where each cell is alive with probability `density`. The
+    /// same `seed` always produces the same board, and the generator then
+    /// carries on driving any stochastic rule.
+    pub fn random(width: u32, height: u32, density: f64, seed: u64) -> Universe {
+        let mut rng = Rng::new(seed);
 
-        let cells = (0..width * height)
+        let cells = (0..cell_count(width, height))
             .map(|_| {
-                if js_sys::Math::random() > 0.5 {
+                if rng.next_f64() < density {
                     Cell::Alive
                 } else {
                     Cell::Dead
@@ -170,7 +742,7 @@ impl Universe {
             })
             .collect();
 
-        Universe { width, height, cells }
+        Universe { cells, rng, ..Universe::with_size(width, height) }
     }
     pub fn width(&self) -> u32 {
         self.width
@@ -180,59 +752,299 @@ impl Universe {
         self.height
     }
 
-    pub fn cells(&self) -> *const Cell {
-        self.cells.as_ptr()
+    /// Pointer to one byte per cell, row by row. For two-state rules each
+    /// byte is a `Cell`; for Generations rules it is the state, where values
+    /// of 2 and up are dying cells.
+    pub fn cells(&self) -> *const u8 {
+        if self.states.is_empty() {
+            self.cells.as_ptr() as *const u8
+        } else {
+            self.states.as_ptr()
+        }
     }
 
-    pub fn toggle_cell(&mut self, row: u32, col: u32){
-        let i = self.get_index(row, col);
-        self.cells[i].toggle();
+    /// Pointer to `changed_count` cell indices, as `u32`s, whose byte in
+    /// `cells()` changed since the last `reset_changed`, plus the cells ants
+    /// moved onto. Ticks and single-cell edits are recorded; changes to the
+    /// size, mode or rule, `clear` and `clear_ants` are not and need a full
+    /// redraw.
+    pub fn changed_cells(&self) -> *const u32 {
+        self.changed.as_ptr()
     }
 
-    pub fn clear(&mut self){
-        let cells: Vec<Cell> = vec![Cell::Dead; self.cells.len()];
-        self.cells = cells;
+    pub fn changed_count(&self) -> usize {
+        self.changed.len()
+    }
+
+    /// Forget the changes recorded so far, typically after drawing them or
+    /// after a full redraw.
+    pub fn reset_changed(&mut self) {
+        self.clear_changed();
+    }
+
+    /// Number of cell states in the current rule, 2 unless it is a
+    /// Generations rule, WireWorld or a turmite with more colours.
+    pub fn state_count(&self) -> u8 {
+        match self.mode {
+            Mode::Life => self.rule.states(),
+            Mode::WireWorld => 4,
+            Mode::Turmite => self.ant_rule.colours(),
+            Mode::Margolus => 2,
+        }
+    }
+
+    /// Replace the rule used by `tick`, given in `B36/S23` or `23/36`
+    /// notation. `WireWorld` switches to WireWorld mode, a turmite rule
+    /// such as `RL` or `RLR` to turmite mode, and any other rule back to
+    /// Life mode. Margolus rules in MCell's `MS,D..` notation switch to
+    /// Margolus mode.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        if rule.trim().eq_ignore_ascii_case(WireCell::RULE) {
+            self.set_mode(Mode::WireWorld);
+            return Ok(());
+        }
+        if AntRule::matches(rule) {
+            self.set_ant_rule(rule.parse::<AntRule>()?);
+            return Ok(());
+        }
+        if MargolusRule::matches(rule) {
+            self.set_margolus_rule(rule.parse::<MargolusRule>()?);
+            return Ok(());
+        }
+        let rule = rule.parse::<Rule>()?;
+        self.set_mode(Mode::Life);
+        self.replace_rule(rule);
+        Ok(())
+    }
+
+    /// The current rule in canonical `B../S..` notation, `WireWorld`, a
+    /// turmite rule or a Margolus rule.
+    pub fn rule(&self) -> String {
+        match self.mode {
+            Mode::Life => self.rule.to_string(),
+            Mode::WireWorld => WireCell::RULE.to_string(),
+            Mode::Turmite => self.ant_rule.to_string(),
+            Mode::Margolus => self.margolus_rule.to_string(),
+        }
+    }
+
+    /// Switch between the life-like rule, WireWorld, turmites and Margolus
+    /// blocks. Live and dying cells become conductors in WireWorld and
+    /// colour 1 for turmites, and every non-empty cell becomes alive going
+    /// back or in Margolus mode.
+    pub fn set_mode(&mut self, mode: Mode) {
+        if mode == self.mode {
+            return;
+        }
+        let occupied: Vec<bool> = self.get_states().iter().map(|&state| state != 0).collect();
+        self.mode = mode;
+        self.cells = vec![Cell::Dead; occupied.len()];
+        self.replace_rule(self.rule.clone());
+        let state = match mode {
+            Mode::Life => 1,
+            Mode::WireWorld => WireCell::Conductor as u8,
+            Mode::Turmite | Mode::Margolus => 1,
+        };
+        for i in (0..occupied.len()).filter(|&i| occupied[i]) {
+            self.write_state(i, state);
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Add an ant facing `heading`. Ants only move in turmite mode, and
+    /// several can share a cell.
+    pub fn add_ant(&mut self, row: u32, col: u32, heading: Heading) -> Result<(), CoordinateError> {
+        self.checked_index(row as i64, col as i64)?;
+        self.ants.push(Ant::new(row, col, heading));
+        Ok(())
     }
 
-    pub fn insert_glider(&mut self, row: u32, col: u32) {
-        let mut i = self.get_index(row - 1, col - 1);
-        self.cells[i] = Cell::Dead;
+    /// The `i`th ant, in the order they were added, if it is still on the
+    /// board.
+    pub fn ant(&self, i: usize) -> Option<Ant> {
+        self.ants.get(i).cloned()
+    }
+
+    pub fn ant_count(&self) -> usize {
+        self.ants.len()
+    }
+
+    pub fn clear_ants(&mut self) {
+        self.ants.clear();
+    }
+
+    /// Place a WireWorld cell. Outside WireWorld mode anything but
+    /// `WireCell::Empty` is a live cell.
+    pub fn set_wire(&mut self, row: u32, col: u32, wire: WireCell) -> Result<(), CoordinateError> {
+        let i = self.checked_index(row as i64, col as i64)?;
+        let state = if self.mode == Mode::WireWorld || wire == WireCell::Empty { wire as u8 } else { 1 };
+        self.write_state(i, state);
+        Ok(())
+    }
+
+    /// Run the current rule on another neighbourhood, keeping its counts.
+    /// Fails if the range is zero or too large, or if the rule is
+    /// non-totalistic and the neighbourhood is not the 3x3 Moore one.
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood, range: u16) -> Result<(), RuleError> {
+        self.rule = self.rule.clone().with_neighborhood(neighborhood, range)?;
+        self.invalidate_tiles();
+        Ok(())
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.rule.neighborhood()
+    }
+
+    pub fn range(&self) -> u16 {
+        self.rule.range()
+    }
 
-        i = self.get_index(row - 1, col);
-        self.cells[i] = Cell::Alive;
+    /// Build a universe from a pattern in RLE format, sized to the `x` and
+    /// `y` of its header and running the rule it names.
+    pub fn from_rle(text: &str) -> Result<Universe, JsValue> {
+        Ok(Universe::from_pattern(&formats::rle::parse(text)?)?)
+    }
 
-        i = self.get_index(row - 1, col + 1);
-        self.cells[i] = Cell::Dead;
+    /// The whole universe in RLE format.
+    pub fn to_rle(&self) -> String {
+        formats::rle::write(&self.to_pattern())
+    }
 
-        i = self.get_index(row, col - 1);
-        self.cells[i] = Cell::Dead;
+    pub fn from_plaintext(text: &str) -> Result<Universe, JsValue> {
+        Ok(Universe::from_pattern(&formats::plaintext::parse(text)?)?)
+    }
 
-        i = self.get_index(row, col);
-        self.cells[i] = Cell::Dead;
+    pub fn to_plaintext(&self) -> String {
+        formats::plaintext::write(&self.to_pattern())
+    }
 
-        i = self.get_index(row, col + 1);
-        self.cells[i] = Cell::Alive;
+    /// Build a universe just large enough for the bounding box of a pattern
+    /// in Life 1.06 format.
+    pub fn from_life106(text: &str) -> Result<Universe, JsValue> {
+        Ok(Universe::from_pattern(&formats::life106::parse(text)?)?)
+    }
 
-        i = self.get_index(row + 1, col - 1);
-        self.cells[i] = Cell::Alive;
+    pub fn to_life106(&self) -> String {
+        formats::life106::write(&self.to_pattern())
+    }
 
-        i = self.get_index(row + 1, col);
-        self.cells[i] = Cell::Alive;
+    /// Read a pattern in macrocell format and flatten it into a universe
+    /// sized to its bounding box, failing if that would be impractically big.
+    pub fn from_macrocell(text: &str) -> Result<Universe, JsValue> {
+        let life = formats::macrocell::parse(text)?;
+        Ok(Universe::from_hashlife(&life, formats::MAX_FLAT_CELLS)?)
+    }
 
-        i = self.get_index(row + 1, col + 1);
-        self.cells[i] = Cell::Alive;
-    }   
+    /// Place an RLE pattern with its top-left corner at `(row, col)`. Fails
+    /// without changing anything if the pattern does not fit.
+    pub fn insert_rle(&mut self, text: &str, row: u32, col: u32) -> Result<(), JsValue> {
+        self.place_pattern(&formats::rle::parse(text)?, row, col)?;
+        Ok(())
+    }
+
+    /// Place a plaintext pattern with its top-left corner at `(row, col)`.
+    /// Fails without changing anything if the pattern does not fit.
+    pub fn insert_plaintext(&mut self, text: &str, row: u32, col: u32) -> Result<(), JsValue> {
+        self.place_pattern(&formats::plaintext::parse(text)?, row, col)?;
+        Ok(())
+    }
+
+    /// Place a Life 1.06 pattern with the top-left corner of its bounding box
+    /// at `(row, col)`. Fails without changing anything if the pattern does
+    /// not fit.
+    pub fn insert_life106(&mut self, text: &str, row: u32, col: u32) -> Result<(), JsValue> {
+        self.place_pattern(&formats::life106::parse(text)?, row, col)?;
+        Ok(())
+    }
+
+    /// Choose how the edges of the universe are joined. `shift` is only
+    /// used by `Topology::TwistedTorus`.
+    pub fn set_topology(&mut self, topology: Topology, shift: u32) {
+        self.topology = topology;
+        self.shift = shift;
+        self.invalidate_tiles();
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn shift(&self) -> u32 {
+        self.shift
+    }
+
+    pub fn toggle_cell(&mut self, row: u32, col: u32) -> Result<(), CoordinateError> {
+        let i = self.checked_index(row as i64, col as i64)?;
+        self.toggle_index(i);
+        Ok(())
+    }
+
+    /// Toggle a cell, mapping coordinates past the edges back through the
+    /// topology. Cells that fall off a dead edge are ignored.
+    pub fn toggle_cell_wrapped(&mut self, row: i32, col: i32) {
+        if let Some(i) = self.resolve(row as i64, col as i64) {
+            self.toggle_index(i);
+        }
+    }
+
+    pub fn clear(&mut self){
+        let cells: Vec<Cell> = vec![Cell::Dead; self.cells.len()];
+        self.cells = cells;
+        self.states.iter_mut().for_each(|state| *state = 0);
+        self.invalidate_tiles();
+    }
+
+    /// Stamp a glider centred on `(row, col)`. Fails without changing
+    /// anything if its 3x3 box does not fit inside the universe.
+    pub fn insert_glider(&mut self, row: u32, col: u32) -> Result<(), CoordinateError> {
+        self.checked_index(row as i64 - 1, col as i64 - 1)?;
+        self.checked_index(row as i64 + 1, col as i64 + 1)?;
+        self.insert_glider_wrapped(row as i32, col as i32);
+        Ok(())
+    }
+
+    /// Stamp a glider centred on `(row, col)`, wrapping its 3x3 box through
+    /// the topology.
+    pub fn insert_glider_wrapped(&mut self, row: i32, col: i32) {
+        let glider = [
+            [Cell::Dead, Cell::Alive, Cell::Dead],
+            [Cell::Dead, Cell::Dead, Cell::Alive],
+            [Cell::Alive, Cell::Alive, Cell::Alive],
+        ];
+        for (d_row, line) in glider.iter().enumerate() {
+            for (d_col, &cell) in line.iter().enumerate() {
+                let (n_row, n_col) = (row as i64 + d_row as i64 - 1, col as i64 + d_col as i64 - 1);
+                if let Some(i) = self.resolve(n_row, n_col) {
+                    self.write_cell(i, cell);
+                }
+            }
+        }
+    }
 
 }
 
+impl Default for Universe {
+    fn default() -> Universe {
+        Universe::new()
+    }
+}
+
 impl fmt::Display for Universe {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for line in self.cells.as_slice().chunks(self.width as usize) {
-            for &cell in line {
-                let sym = if cell == Cell::Dead {'◻'} else {'◼'};
+        for line in self.get_states().chunks(self.width as usize) {
+            for &state in line {
+                let sym = match (self.mode, state) {
+                    (_, 0) => '◻',
+                    (_, 1) => '◼',
+                    (Mode::WireWorld, 3) => '▦',
+                    _ => '◩',
+                };
                 write!(f, "{}", sym)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -0,0 +1,48 @@
+use crate::{Neighborhood, Universe};
+
+impl Universe {
+    /// Live cells in the `(2r+1)x(2r+1)` box around every cell, row by row,
+    /// for Moore rules with a range `r` above 1. Returns `None` for other
+    /// rules, whose neighbourhoods are small or not boxes.
+    ///
+    /// The board plus an `r`-cell margin, resolved through the topology, is
+    /// summed into a summed-area table once. Each box is then four lookups
+    /// instead of `(2r+1)^2`, which is what makes Larger than Life rules
+    /// such as `R5,C0,M1,S34..58,B34..45,NM` affordable.
+    pub(crate) fn box_counts(&self) -> Option<Vec<u32>> {
+        if self.rule.neighborhood() != Neighborhood::Moore || self.rule.range() < 2 {
+            return None;
+        }
+        let r = self.rule.range() as i64;
+        let (width, height) = (self.width as i64 + 2 * r, self.height as i64 + 2 * r);
+
+        // `sums[y * stride + x]` is the number of live cells above and to the
+        // left of padded cell `(y, x)`, with a zero row and column in front.
+        let stride = width as usize + 1;
+        let mut sums = vec![0u32; stride * (height as usize + 1)];
+        for y in 0..height as usize {
+            let mut row_sum = 0;
+            for x in 0..width as usize {
+                if let Some(i) = self.resolve(y as i64 - r, x as i64 - r) {
+                    row_sum += self.cells[i] as u32;
+                }
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+            }
+        }
+
+        let side = 2 * r as usize + 1;
+        let middle = self.rule.includes_middle();
+        let mut counts = Vec::with_capacity(self.cells.len());
+        for row in 0..self.height as usize {
+            for col in 0..self.width as usize {
+                let (bottom, right) = (row + side, col + side);
+                let total = sums[bottom * stride + right] + sums[row * stride + col]
+                    - sums[row * stride + right]
+                    - sums[bottom * stride + col];
+                let own = if middle { 0 } else { self.cells[row * self.width as usize + col] as u32 };
+                counts.push(total - own);
+            }
+        }
+        Some(counts)
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Cell, RuleError, Universe};
+
+/// A block cellular automaton on the Margolus neighbourhood. The board is
+/// split into 2x2 blocks, shifted by one cell diagonally every other
+/// generation, and each block is replaced through a 16-entry table.
+///
+/// A block is numbered by its live cells: 1 for the top-left, 2 for the
+/// top-right, 4 for the bottom-left and 8 for the bottom-right, as in
+/// MCell's `MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15` notation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MargolusRule {
+    table: [u8; 16],
+}
+
+impl MargolusRule {
+    /// Fails unless every entry is a block number, below 16.
+    pub fn new(table: [u8; 16]) -> Result<MargolusRule, RuleError> {
+        match table.iter().find(|&&block| block > 15) {
+            Some(&block) => Err(RuleError::InvalidBlock(block)),
+            None => Ok(MargolusRule { table }),
+        }
+    }
+
+    /// Fredkin and Toffoli's billiard-ball machine.
+    pub fn billiard_ball() -> MargolusRule {
+        MargolusRule {
+            table: [0, 8, 4, 3, 2, 5, 9, 7, 1, 6, 10, 11, 12, 13, 14, 15],
+        }
+    }
+
+    /// Margolus's Critters.
+    pub fn critters() -> MargolusRule {
+        MargolusRule {
+            table: [15, 14, 13, 3, 11, 5, 6, 1, 7, 9, 10, 2, 12, 4, 8, 0],
+        }
+    }
+
+    /// Tron, which flips blocks that are all dead or all alive.
+    pub fn tron() -> MargolusRule {
+        MargolusRule {
+            table: [15, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 0],
+        }
+    }
+
+    pub fn next(&self, block: u8) -> u8 {
+        self.table[block as usize]
+    }
+
+    /// Whether the table is a permutation, so every generation has exactly
+    /// one predecessor.
+    pub fn is_reversible(&self) -> bool {
+        let mut seen = [false; 16];
+        self.table.iter().for_each(|&block| seen[block as usize] = true);
+        seen.iter().all(|&seen| seen)
+    }
+
+    /// The table undoing this one, if it is reversible.
+    pub fn inverse(&self) -> Option<MargolusRule> {
+        if !self.is_reversible() {
+            return None;
+        }
+        let mut table = [0; 16];
+        for (block, &next) in self.table.iter().enumerate() {
+            table[next as usize] = block as u8;
+        }
+        Some(MargolusRule { table })
+    }
+
+    /// Whether `s` looks like a Margolus rule rather than a life-like one.
+    pub(crate) fn matches(s: &str) -> bool {
+        s.trim().get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("MS,"))
+    }
+}
+
+impl Default for MargolusRule {
+    fn default() -> MargolusRule {
+        MargolusRule::billiard_ball()
+    }
+}
+
+impl FromStr for MargolusRule {
+    type Err = RuleError;
+
+    /// Parses MCell's `MS,D` followed by the 16 entries separated by `;`.
+    fn from_str(s: &str) -> Result<MargolusRule, RuleError> {
+        let malformed = || RuleError::Malformed(s.to_string());
+        let entries = s
+            .trim()
+            .get(..4)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("MS,D"))
+            .map(|_| &s.trim()[4..])
+            .ok_or_else(malformed)?;
+        let entries = entries
+            .split(';')
+            .map(|entry| entry.trim().parse::<u8>().map_err(|_| malformed()))
+            .collect::<Result<Vec<u8>, RuleError>>()?;
+        if entries.len() != 16 {
+            return Err(malformed());
+        }
+        let mut table = [0; 16];
+        table.copy_from_slice(&entries);
+        MargolusRule::new(table)
+    }
+}
+
+impl fmt::Display for MargolusRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MS,D")?;
+        for (i, block) in self.table.iter().enumerate() {
+            if i > 0 {
+                write!(f, ";")?;
+            }
+            write!(f, "{}", block)?;
+        }
+        Ok(())
+    }
+}
+
+impl Universe {
+    /// Replace every block of the current phase through `rule`. Blocks
+    /// start on even rows and columns in phase 0 and odd ones in phase 1;
+    /// on an odd-sized board the last row or column is left out of one of
+    /// the phases. Blocks that would cross a dead edge are left unchanged.
+    pub(crate) fn step_blocks(&mut self, rule: &MargolusRule) {
+        let offset = self.phase as i64;
+        for block_row in 0..(self.height / 2) as i64 {
+            for block_col in 0..(self.width / 2) as i64 {
+                let (row, col) = (offset + 2 * block_row, offset + 2 * block_col);
+                let corners = [(row, col), (row, col + 1), (row + 1, col), (row + 1, col + 1)];
+                let indices: Option<Vec<usize>> = corners.iter().map(|&(r, c)| self.resolve(r, c)).collect();
+                let indices = match indices {
+                    Some(indices) => indices,
+                    None => continue,
+                };
+                let block = indices
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &i)| self.cells[i] == Cell::Alive)
+                    .fold(0, |block, (bit, _)| block | 1 << bit);
+                let next = rule.next(block);
+                for (bit, &i) in indices.iter().enumerate() {
+                    let cell = if next & 1 << bit != 0 { Cell::Alive } else { Cell::Dead };
+                    self.write_cell(i, cell);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use wasm_bindgen::prelude::*;
+
+/// Which cells around a cell count as its neighbours.
+///
+/// Each neighbourhood has a range `r`. At range 1 Moore is the usual 3x3
+/// block, von Neumann the four orthogonal cells and hexagonal six cells.
+/// The hexagonal grid is drawn on square cells by skewing it: a cell's
+/// neighbours are its Moore neighbours except the north-east and
+/// south-west ones.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Neighborhood {
+    /// Cells within `r` steps in both directions, a `(2r+1)x(2r+1)` square.
+    #[default]
+    Moore = 0,
+    /// Cells within `r` orthogonal steps, a diamond.
+    VonNeumann = 1,
+    /// Cells within `r` steps on the hexagonal grid.
+    Hexagonal = 2,
+}
+
+impl Neighborhood {
+    /// The `(row, col)` offsets of every neighbour at range `range`, row by
+    /// row and excluding the cell itself.
+    pub fn offsets(self, range: u16) -> Vec<(i64, i64)> {
+        let r = range as i64;
+        let mut offsets = Vec::new();
+        for d_row in -r..=r {
+            for d_col in -r..=r {
+                let inside = match self {
+                    Neighborhood::Moore => true,
+                    Neighborhood::VonNeumann => d_row.abs() + d_col.abs() <= r,
+                    Neighborhood::Hexagonal => (d_row - d_col).abs() <= r,
+                };
+                if inside && (d_row, d_col) != (0, 0) {
+                    offsets.push((d_row, d_col));
+                }
+            }
+        }
+        offsets
+    }
+
+    /// Number of neighbours at range `range`.
+    pub fn size(self, range: u16) -> u32 {
+        let r = range as u32;
+        match self {
+            Neighborhood::Moore => (2 * r + 1) * (2 * r + 1) - 1,
+            Neighborhood::VonNeumann => 2 * r * (r + 1),
+            Neighborhood::Hexagonal => 3 * r * (r + 1),
+        }
+    }
+
+    /// Suffix of a range 1 B/S rulestring, as in `B2/S34H`.
+    pub(crate) fn suffix(self) -> &'static str {
+        match self {
+            Neighborhood::Moore => "",
+            Neighborhood::VonNeumann => "V",
+            Neighborhood::Hexagonal => "H",
+        }
+    }
+
+    /// The `N` field of a HROT rulestring, as in `R2,C0,S2-3,B3,NN`.
+    pub(crate) fn hrot_code(self) -> &'static str {
+        match self {
+            Neighborhood::Moore => "NM",
+            Neighborhood::VonNeumann => "NN",
+            Neighborhood::Hexagonal => "NH",
+        }
+    }
+}
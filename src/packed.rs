@@ -0,0 +1,258 @@
+use wasm_bindgen::prelude::*;
+
+use crate::topology::checked_index;
+use crate::{cell_count, Cell, CoordinateError, Rule, Universe};
+
+const WORD_BITS: u32 = 32;
+
+/// A universe that stores one bit per cell instead of one byte.
+///
+/// Each row is packed into `words_per_row` little-endian `u32` words: the cell
+/// at `(row, col)` is bit `col % 32` of word `row * words_per_row + col / 32`.
+/// Padding bits past `width` in the last word of a row are always zero.
+///
+/// From JS the words can be read directly out of `memory.buffer`:
+///
+/// ```js
+/// const words = new Uint32Array(memory.buffer, universe.cells(), universe.cells_len());
+/// const wpr = universe.words_per_row();
+/// const alive = (words[row * wpr + (col >> 5)] >>> (col & 31)) & 1;
+/// ```
+#[wasm_bindgen]
+pub struct PackedUniverse {
+    width: u32,
+    height: u32,
+    words_per_row: usize,
+    words: Vec<u32>,
+    rule: Rule,
+}
+
+impl PackedUniverse {
+    fn word_and_bit(&self, row: u32, col: u32) -> (usize, u32) {
+        (
+            row as usize * self.words_per_row + (col / WORD_BITS) as usize,
+            col % WORD_BITS,
+        )
+    }
+
+    fn row(&self, row: u32) -> &[u32] {
+        let start = row as usize * self.words_per_row;
+        &self.words[start..start + self.words_per_row]
+    }
+
+    /// Mask of the valid bits in the last word of each row.
+    fn last_word_mask(&self) -> u32 {
+        match self.width % WORD_BITS {
+            0 => !0,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// `out` bit `c` becomes `row` bit `c - 1`, wrapping around the row.
+    fn shift_west(&self, row: &[u32], out: &mut [u32]) {
+        let n = row.len();
+        for i in 0..n {
+            let carry = if i > 0 { row[i - 1] >> (WORD_BITS - 1) } else { 0 };
+            out[i] = (row[i] << 1) | carry;
+        }
+        let last = self.width - 1;
+        out[0] |= (row[(last / WORD_BITS) as usize] >> (last % WORD_BITS)) & 1;
+        out[n - 1] &= self.last_word_mask();
+    }
+
+    /// `out` bit `c` becomes `row` bit `c + 1`, wrapping around the row.
+    fn shift_east(&self, row: &[u32], out: &mut [u32]) {
+        let n = row.len();
+        for i in 0..n {
+            let carry = if i + 1 < n { row[i + 1] << (WORD_BITS - 1) } else { 0 };
+            out[i] = (row[i] >> 1) | carry;
+        }
+        let last = self.width - 1;
+        out[(last / WORD_BITS) as usize] |= (row[0] & 1) << (last % WORD_BITS);
+    }
+
+    pub fn get_cells(&self) -> Vec<Cell> {
+        let mut cells = Vec::with_capacity(cell_count(self.width, self.height));
+        for row in 0..self.height {
+            for col in 0..self.width {
+                cells.push(if self.is_alive(row, col) { Cell::Alive } else { Cell::Dead });
+            }
+        }
+        cells
+    }
+
+    fn check(&self, row: u32, col: u32) -> Result<(), CoordinateError> {
+        checked_index(row as i64, col as i64, self.width, self.height).map(|_| ())
+    }
+
+    /// Set cells to be alive by passing the row and column of each cell.
+    /// Nothing is changed if any cell lies outside the universe.
+    pub fn set_cells(&mut self, cells: &[(u32, u32)]) -> Result<(), CoordinateError> {
+        for &(row, col) in cells.iter() {
+            self.check(row, col)?;
+        }
+        for &(row, col) in cells.iter() {
+            let (word, bit) = self.word_and_bit(row, col);
+            self.words[word] |= 1 << bit;
+        }
+        Ok(())
+    }
+}
+
+impl From<&Universe> for PackedUniverse {
+    /// Copies the cells of `universe`. A rule it cannot run is replaced by
+    /// Conway's Game of Life.
+    fn from(universe: &Universe) -> PackedUniverse {
+        let mut packed = PackedUniverse::new(universe.width, universe.height);
+        packed.rule = universe.rule.clone().require_life_like().unwrap_or_default();
+        for row in 0..universe.height {
+            for col in 0..universe.width {
+                if universe.cells[universe.get_index(row, col)] == Cell::Alive {
+                    let (word, bit) = packed.word_and_bit(row, col);
+                    packed.words[word] |= 1 << bit;
+                }
+            }
+        }
+        packed
+    }
+}
+
+#[wasm_bindgen]
+impl PackedUniverse {
+    /// An all-dead universe running Conway's Game of Life. Panics if the
+    /// board has more cells than a `u32` can index, like `Universe`.
+    pub fn new(width: u32, height: u32) -> PackedUniverse {
+        cell_count(width, height);
+        let words_per_row = width.div_ceil(WORD_BITS) as usize;
+        let words = words_per_row
+            .checked_mul(height as usize)
+            .unwrap_or_else(|| panic!("a {}x{} universe has too many cells", width, height));
+        PackedUniverse {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words],
+            rule: Rule::default(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn words_per_row(&self) -> usize {
+        self.words_per_row
+    }
+
+    /// Pointer to the packed cell words, see the type-level docs for layout.
+    pub fn cells(&self) -> *const u32 {
+        self.words.as_ptr()
+    }
+
+    /// Number of `u32` words behind `cells()`.
+    pub fn cells_len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether a cell is alive; cells outside the universe are dead.
+    pub fn is_alive(&self, row: u32, col: u32) -> bool {
+        if self.check(row, col).is_err() {
+            return false;
+        }
+        let (word, bit) = self.word_and_bit(row, col);
+        (self.words[word] >> bit) & 1 == 1
+    }
+
+    pub fn toggle_cell(&mut self, row: u32, col: u32) -> Result<(), CoordinateError> {
+        self.check(row, col)?;
+        let (word, bit) = self.word_and_bit(row, col);
+        self.words[word] ^= 1 << bit;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// Replace the rule used by `tick`. Generations and non-totalistic
+    /// rules are rejected.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        self.rule = rule.parse::<Rule>()?.require_life_like()?;
+        Ok(())
+    }
+
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+
+    /// Advance one generation, counting neighbours for 32 cells at a time
+    /// with a bitwise ripple-carry adder.
+    pub fn tick(&mut self) {
+        let _timer = crate::Timer::new("PackedUniverse::tick");
+
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let wpr = self.words_per_row;
+        let mut west = vec![0; self.words.len()];
+        let mut east = vec![0; self.words.len()];
+        for row in 0..self.height {
+            let range = row as usize * wpr..(row as usize + 1) * wpr;
+            self.shift_west(self.row(row), &mut west[range.clone()]);
+            self.shift_east(self.row(row), &mut east[range]);
+        }
+
+        let births: Vec<u32> = (0..9).filter(|&n| self.rule.birth()[n as usize]).collect();
+        let survivals: Vec<u32> = (0..9).filter(|&n| self.rule.survival()[n as usize]).collect();
+        let last_mask = self.last_word_mask();
+
+        let mut future = vec![0; self.words.len()];
+        for row in 0..self.height as usize {
+            let up = (row + self.height as usize - 1) % self.height as usize;
+            let down = (row + 1) % self.height as usize;
+
+            for w in 0..wpr {
+                let (i, u, d) = (row * wpr + w, up * wpr + w, down * wpr + w);
+                let neighbours = [
+                    self.words[u], west[u], east[u],
+                    west[i], east[i],
+                    self.words[d], west[d], east[d],
+                ];
+
+                // Four bit-planes holding the neighbour count of each cell.
+                let (mut s0, mut s1, mut s2, mut s3) = (0u32, 0u32, 0u32, 0u32);
+                for &x in neighbours.iter() {
+                    let c0 = s0 & x;
+                    s0 ^= x;
+                    let c1 = s1 & c0;
+                    s1 ^= c0;
+                    let c2 = s2 & c1;
+                    s2 ^= c1;
+                    s3 |= c2;
+                }
+                let equals = |n: u32| {
+                    let bit = |plane: u32, b: u32| if n >> b & 1 == 1 { plane } else { !plane };
+                    bit(s0, 0) & bit(s1, 1) & bit(s2, 2) & bit(s3, 3)
+                };
+
+                let cell = self.words[i];
+                let mut next = 0;
+                for &n in births.iter() {
+                    next |= equals(n) & !cell;
+                }
+                for &n in survivals.iter() {
+                    next |= equals(n) & cell;
+                }
+                if w == wpr - 1 {
+                    next &= last_mask;
+                }
+                future[i] = next;
+            }
+        }
+        self.words = future;
+    }
+}
@@ -0,0 +1,571 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::isotropic::Configs;
+use crate::{Cell, Neighborhood};
+
+/// Largest neighbourhood range a rule can have, the same limit as Golly.
+pub const MAX_RANGE: u16 = 500;
+
+/// Largest neighbour count any rule can refer to: the range `MAX_RANGE`
+/// Moore neighbourhood plus the middle cell.
+const MAX_COUNT: u32 = (2 * MAX_RANGE as u32 + 1) * (2 * MAX_RANGE as u32 + 1);
+
+/// An outer-totalistic rule in B/S notation, e.g. `B3/S23` for Conway's
+/// Game of Life or `B36/S23` for HighLife.
+///
+/// The rule is stored as two lookup tables indexed by the number of live
+/// neighbours, so applying it in `tick` is a single array access.
+///
+/// A Generations rule such as Brian's Brain (`/2/3` or `B2/S/C3`) has more
+/// than two `states`: a live cell that does not survive passes through
+/// `states - 2` dying states before it is dead, and only fully live cells
+/// count as neighbours.
+///
+/// Isotropic non-totalistic rules in Hensel notation, such as `B2-a/S12`
+/// or tlife (`B3/S2-i34q`), look at how the live neighbours are arranged
+/// rather than just how many there are. They keep a second pair of tables
+/// indexed by neighbour configuration, see `next_configuration`.
+///
+/// Rules may also use another `Neighborhood`, written with a suffix at
+/// range 1 (`B2/S34H` for hexagonal, `B2/S013V` for von Neumann) and in HROT
+/// notation at larger ranges (`R2,C0,S2-3,B3,NN`). Larger than Life rules
+/// such as Bosco's rule (`R5,C0,M1,S34..58,B34..45,NM`) are HROT rules that
+/// may count the cell itself, the `M1` field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    birth: Counts,
+    survival: Counts,
+    isotropic: Option<Isotropic>,
+    neighborhood: Neighborhood,
+    range: u16,
+    middle: bool,
+    states: u8,
+}
+
+/// A set of neighbour counts, stored as a bitset that grows with the
+/// largest count inserted. Indexing it gives whether a count is in the set.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Counts(Vec<u64>);
+
+impl Counts {
+    pub fn contains(&self, count: u32) -> bool {
+        match self.0.get(count as usize / 64) {
+            Some(word) => (word >> (count % 64)) & 1 == 1,
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, count: u32) {
+        let word = count as usize / 64;
+        if word >= self.0.len() {
+            self.0.resize(word + 1, 0);
+        }
+        self.0[word] |= 1 << (count % 64);
+    }
+
+    /// The counts in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.0.len() as u32 * 64).filter(move |&n| self.contains(n))
+    }
+}
+
+impl std::ops::Index<usize> for Counts {
+    type Output = bool;
+
+    fn index(&self, count: usize) -> &bool {
+        if count <= MAX_COUNT as usize && self.contains(count as u32) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Isotropic {
+    birth: Configs,
+    survival: Configs,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    /// A character that is not a neighbour count (0-8) was found.
+    InvalidDigit(char),
+    /// The rulestring does not have the shape `B../S..`, `../..` or
+    /// `R..,C..,S..,B..`.
+    Malformed(String),
+    /// A neighbourhood range that is zero or above `MAX_RANGE`.
+    InvalidRange(u32),
+    /// A Hensel letter that does not exist for the neighbour count before
+    /// it, such as `1a`.
+    InvalidLetter(u8, char),
+    /// The rule is valid but cannot be run by the requested engine.
+    Unsupported(String),
+    /// A Margolus table entry that is not a block number, 0 to 15.
+    InvalidBlock(u8),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::InvalidDigit(c) => write!(f, "invalid neighbour count '{}' in rule", c),
+            RuleError::Malformed(rule) => write!(f, "malformed rulestring \"{}\"", rule),
+            RuleError::InvalidRange(range) => write!(f, "neighbourhood range {} is out of bounds", range),
+            RuleError::InvalidLetter(count, letter) => {
+                write!(f, "invalid neighbourhood letter '{}' after {} in rule", letter, count)
+            }
+            RuleError::Unsupported(rule) => write!(f, "rule {} is not supported here", rule),
+            RuleError::InvalidBlock(block) => write!(f, "Margolus block {} is not between 0 and 15", block),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl From<RuleError> for JsValue {
+    fn from(err: RuleError) -> JsValue {
+        js_sys::Error::new(&err.to_string()).into()
+    }
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub fn conway() -> Rule {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    /// A two-state rule on the 3x3 Moore neighbourhood. Counts above the
+    /// largest neighbourhood allowed by `MAX_RANGE` are ignored.
+    pub fn new(birth: &[u32], survival: &[u32]) -> Rule {
+        let mut rule = Rule {
+            birth: Counts::default(),
+            survival: Counts::default(),
+            isotropic: None,
+            neighborhood: Neighborhood::Moore,
+            range: 1,
+            middle: false,
+            states: 2,
+        };
+        for &n in birth.iter().filter(|&&n| n <= MAX_COUNT) {
+            rule.birth.insert(n);
+        }
+        for &n in survival.iter().filter(|&&n| n <= MAX_COUNT) {
+            rule.survival.insert(n);
+        }
+        rule
+    }
+
+    /// A Generations rule with `states` states in total, including dead and
+    /// alive.
+    pub fn generations(birth: &[u32], survival: &[u32], states: u8) -> Rule {
+        Rule {
+            states: states.max(2),
+            ..Rule::new(birth, survival)
+        }
+    }
+
+    /// A rule from the sets of neighbour configurations that give birth and
+    /// survival. It is stored as totalistic if the sets allow it.
+    fn from_configs(birth: Configs, survival: Configs, states: u8) -> Rule {
+        let mut rule = Rule {
+            isotropic: Some(Isotropic { birth, survival }),
+            states,
+            ..Rule::new(&[], &[])
+        };
+        let mut totalistic = true;
+        for n in 0..9 {
+            let all = Configs::all(n as u8);
+            for (table, configs) in [(&mut rule.birth, birth), (&mut rule.survival, survival)].iter_mut() {
+                let some = configs.intersection(all);
+                if !some.is_empty() {
+                    table.insert(n as u32);
+                }
+                totalistic &= some.is_empty() || some == all;
+            }
+        }
+        if totalistic {
+            rule.isotropic = None;
+        }
+        rule
+    }
+
+    /// The state of a cell in the next generation given its current state
+    /// and how many of its neighbours are alive. Only meaningful for
+    /// totalistic rules.
+    pub fn next(&self, cell: Cell, alive_count: u32) -> Cell {
+        let table = match cell {
+            Cell::Alive => &self.survival,
+            Cell::Dead => &self.birth,
+        };
+        if table.contains(alive_count) {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+
+    /// Like `next`, for the state byte of a Generations rule: 0 is dead, 1
+    /// is alive and anything higher is dying.
+    pub fn next_state(&self, state: u8, alive_count: u32) -> u8 {
+        let (born, survives) = (self.birth.contains(alive_count), self.survival.contains(alive_count));
+        self.advance(state, born, survives)
+    }
+
+    /// The state of a cell in the next generation given which of its
+    /// neighbours are alive, one bit each from north-west to south-east
+    /// row by row, skipping the cell itself.
+    pub fn next_configuration(&self, cell: Cell, neighbours: u8) -> Cell {
+        match self.advance(cell as u8, self.born(neighbours), self.survives(neighbours)) {
+            1 => Cell::Alive,
+            _ => Cell::Dead,
+        }
+    }
+
+    /// Like `next_configuration`, for the state byte of a Generations rule.
+    pub fn next_state_configuration(&self, state: u8, neighbours: u8) -> u8 {
+        self.advance(state, self.born(neighbours), self.survives(neighbours))
+    }
+
+    fn born(&self, neighbours: u8) -> bool {
+        match self.isotropic {
+            Some(isotropic) => isotropic.birth.contains(neighbours),
+            None => self.birth.contains(neighbours.count_ones()),
+        }
+    }
+
+    fn survives(&self, neighbours: u8) -> bool {
+        match self.isotropic {
+            Some(isotropic) => isotropic.survival.contains(neighbours),
+            None => self.survival.contains(neighbours.count_ones()),
+        }
+    }
+
+    fn advance(&self, state: u8, born: bool, survives: bool) -> u8 {
+        match state {
+            0 => born as u8,
+            1 if survives => 1,
+            // Cannot overflow, and states past the last one die.
+            _ => state.checked_add(1).filter(|&next| next < self.states).unwrap_or(0),
+        }
+    }
+
+    /// Whether the rule only depends on the number of live neighbours.
+    pub fn is_totalistic(&self) -> bool {
+        self.isotropic.is_none()
+    }
+
+    /// Number of cell states, 2 unless this is a Generations rule.
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// The same rule on another neighbourhood. Non-totalistic rules only
+    /// exist on the range 1 Moore neighbourhood.
+    pub fn with_neighborhood(self, neighborhood: Neighborhood, range: u16) -> Result<Rule, RuleError> {
+        if range == 0 || range > MAX_RANGE {
+            return Err(RuleError::InvalidRange(range as u32));
+        }
+        if !self.is_totalistic() && (neighborhood, range) != (Neighborhood::Moore, 1) {
+            return Err(RuleError::Unsupported(self.to_string()));
+        }
+        Ok(Rule {
+            neighborhood,
+            range,
+            ..self
+        })
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    pub fn range(&self) -> u16 {
+        self.range
+    }
+
+    /// Whether a live cell counts towards its own neighbour count.
+    pub fn includes_middle(&self) -> bool {
+        self.middle
+    }
+
+    /// Offsets of the cells to count, including `(0, 0)` if the rule counts
+    /// the middle cell, or an empty list for the plain 3x3 Moore
+    /// neighbourhood, which engines handle directly.
+    pub(crate) fn offsets(&self) -> Vec<(i64, i64)> {
+        match (self.neighborhood, self.range, self.middle) {
+            (Neighborhood::Moore, 1, false) => Vec::new(),
+            (neighborhood, range, middle) => {
+                let mut offsets = neighborhood.offsets(range);
+                if middle {
+                    offsets.push((0, 0));
+                }
+                offsets
+            }
+        }
+    }
+
+    /// Reject rules that only a per-cell `Universe` can run: Generations,
+    /// non-totalistic rules and other neighbourhoods than 3x3 Moore.
+    pub(crate) fn require_life_like(self) -> Result<Rule, RuleError> {
+        if self.states > 2 || !self.is_totalistic() || !self.offsets().is_empty() {
+            return Err(RuleError::Unsupported(self.to_string()));
+        }
+        Ok(self)
+    }
+
+    /// Neighbour counts giving birth. For a non-totalistic rule a count is
+    /// included if any configuration with that many neighbours gives birth.
+    pub fn birth(&self) -> &Counts {
+        &self.birth
+    }
+
+    pub fn survival(&self) -> &Counts {
+        &self.survival
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::conway()
+    }
+}
+
+/// Parse the conditions of one half of a rule, such as `2-a34q`. Each digit
+/// may be followed by Hensel letters to include only those configurations,
+/// or by `-` and letters to exclude them.
+fn parse_conditions(conditions: &str) -> Result<Configs, RuleError> {
+    let mut configs = Configs::default();
+    let mut chars = conditions.chars().peekable();
+    while let Some(c) = chars.next() {
+        let count = match c.to_digit(10) {
+            Some(n) if n <= 8 => n as u8,
+            _ => return Err(RuleError::InvalidDigit(c)),
+        };
+        let negate = chars.peek() == Some(&'-');
+        if negate {
+            chars.next();
+        }
+        let mut letters: Option<Configs> = None;
+        while let Some(&letter) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+            chars.next();
+            let more = Configs::letter(count, letter).ok_or(RuleError::InvalidLetter(count, letter))?;
+            letters = Some(letters.unwrap_or_default().union(more));
+        }
+        let all = Configs::all(count);
+        configs = configs.union(match letters {
+            Some(letters) if negate => all.difference(letters),
+            Some(letters) => letters,
+            None if negate => return Err(RuleError::Malformed(conditions.to_string())),
+            None => all,
+        });
+    }
+    Ok(configs)
+}
+
+fn parse_states(digits: &str) -> Result<u8, RuleError> {
+    match digits.parse::<u8>() {
+        Ok(states) if states >= 2 => Ok(states),
+        _ => Err(RuleError::Malformed(digits.to_string())),
+    }
+}
+
+/// Parse an item of a HROT count list, such as `5`, `2-3` or the Larger
+/// than Life form `34..58`, into `counts`.
+fn parse_count_list(list: &str, counts: &mut Vec<u32>) -> Result<(), RuleError> {
+    if list.is_empty() {
+        return Ok(());
+    }
+    let malformed = || RuleError::Malformed(list.to_string());
+    let (low, high) = match (list.find(".."), list.find('-')) {
+        (Some(dots), _) => (&list[..dots], &list[dots + 2..]),
+        (None, Some(dash)) => (&list[..dash], &list[dash + 1..]),
+        (None, None) => (list, list),
+    };
+    let low = low.parse::<u32>().map_err(|_| malformed())?;
+    let high = high.parse::<u32>().map_err(|_| malformed())?;
+    if high > MAX_COUNT {
+        return Err(malformed());
+    }
+    counts.extend(low..=high);
+    Ok(())
+}
+
+/// Parse a HROT rulestring such as `R2,C0,S2-3,B3,NN`: the range, number
+/// of states (0 and 2 both mean two), whether the middle cell is counted
+/// (`M0` or `M1`, optional), survival and birth counts, and an optional
+/// neighbourhood (`NM`, `NN` or `NH`; Moore by default).
+fn parse_hrot(s: &str) -> Result<Rule, RuleError> {
+    let malformed = || RuleError::Malformed(s.to_string());
+    let (mut range, mut states, mut neighborhood) = (None, 2, Neighborhood::Moore);
+    let mut middle = false;
+    let mut counts = [Vec::new(), Vec::new()];
+    let mut current = None;
+    for field in s.split(',').map(str::trim) {
+        let split = field.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(field.len());
+        let (key, value) = field.split_at(split);
+        match key.to_ascii_uppercase().as_str() {
+            "R" => {
+                let r = value.parse::<u32>().map_err(|_| malformed())?;
+                range = Some(u16::try_from(r).map_err(|_| RuleError::InvalidRange(r))?);
+            }
+            "C" if value == "0" => states = 2,
+            "C" => states = parse_states(value)?,
+            "M" if value == "0" || value == "1" => middle = value == "1",
+            "S" | "B" => {
+                let list = if key.eq_ignore_ascii_case("S") { 0 } else { 1 };
+                parse_count_list(value, &mut counts[list])?;
+                current = Some(list);
+            }
+            "NM" if value.is_empty() => neighborhood = Neighborhood::Moore,
+            "NN" if value.is_empty() => neighborhood = Neighborhood::VonNeumann,
+            "NH" if value.is_empty() => neighborhood = Neighborhood::Hexagonal,
+            "" if !value.is_empty() => parse_count_list(value, &mut counts[current.ok_or_else(malformed)?])?,
+            _ => return Err(malformed()),
+        }
+    }
+    let [survival, birth] = counts;
+    let rule = Rule {
+        middle,
+        ..Rule::generations(&birth, &survival, states)
+    };
+    rule.with_neighborhood(neighborhood, range.ok_or_else(malformed)?)
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    /// Parses `B36/S23` and the older `23/36` (survival/birth) notation,
+    /// plus the Generations forms `B2/S345/C4` and `345/2/4`. Counts may
+    /// carry Hensel letters, as in `B2-a/S12`. The `B`, `S` and `C` markers
+    /// are case-insensitive and the `/` may be omitted in B/S form. A
+    /// trailing `H` or `V` selects the hexagonal or von Neumann
+    /// neighbourhood, and HROT notation gives larger ranges.
+    fn from_str(s: &str) -> Result<Rule, RuleError> {
+        let s = s.trim();
+        if s.starts_with(|c| "Rr".contains(c)) {
+            return parse_hrot(s);
+        }
+        let (s, neighborhood) = match s.chars().last() {
+            Some('H') | Some('h') => (&s[..s.len() - 1], Neighborhood::Hexagonal),
+            Some('V') | Some('v') => (&s[..s.len() - 1], Neighborhood::VonNeumann),
+            _ => (s, Neighborhood::Moore),
+        };
+        parse_life_like(s)?.with_neighborhood(neighborhood, 1)
+    }
+}
+
+/// Parse the range 1 B/S and S/B notations without a neighbourhood suffix.
+fn parse_life_like(s: &str) -> Result<Rule, RuleError> {
+    let malformed = || RuleError::Malformed(s.to_string());
+
+    if s.starts_with(|c| "BbSs".contains(c)) {
+        // Birth, survival and states sections. A lowercase `c` is a
+        // Hensel letter unless it starts a section of its own.
+        let mut sections: [Option<String>; 3] = [None, None, None];
+        let mut current = None;
+        for c in s.chars() {
+            let marker = match c {
+                'B' | 'b' => Some(0),
+                'S' | 's' => Some(1),
+                'C' => Some(2),
+                'c' if current.is_none() => Some(2),
+                _ => None,
+            };
+            match (marker, current) {
+                (Some(m), _) if sections[m].is_some() => return Err(malformed()),
+                (Some(m), _) => {
+                    sections[m] = Some(String::new());
+                    current = Some(m);
+                }
+                (None, _) if c == '/' => current = None,
+                (None, Some(m)) => sections[m].as_mut().unwrap().push(c),
+                (None, None) => return Err(malformed()),
+            }
+        }
+        let [birth, survival, states] = sections;
+        let (birth, survival) = (birth.ok_or_else(malformed)?, survival.ok_or_else(malformed)?);
+        let states = states.map_or(Ok(2), |states| parse_states(&states))?;
+        return Ok(Rule::from_configs(parse_conditions(&birth)?, parse_conditions(&survival)?, states));
+    }
+
+    let parts: Vec<&str> = s.split('/').collect();
+    let (survival, birth, states) = match parts.as_slice() {
+        [survival, birth] => (survival, birth, 2),
+        [survival, birth, states] => (survival, birth, parse_states(states)?),
+        _ => return Err(malformed()),
+    };
+    Ok(Rule::from_configs(parse_conditions(birth)?, parse_conditions(survival)?, states))
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.range > 1 || self.middle {
+            write!(f, "R{},C{},", self.range, if self.states > 2 { self.states } else { 0 })?;
+            if self.middle {
+                write!(f, "M1,")?;
+            }
+            write!(f, "S")?;
+            write_count_list(f, &self.survival)?;
+            write!(f, ",B")?;
+            write_count_list(f, &self.birth)?;
+            return write!(f, ",{}", self.neighborhood.hrot_code());
+        }
+        let (birth, survival) = match self.isotropic {
+            Some(isotropic) => (Some(isotropic.birth), Some(isotropic.survival)),
+            None => (None, None),
+        };
+        write!(f, "B")?;
+        write_conditions(f, &self.birth, birth)?;
+        write!(f, "/S")?;
+        write_conditions(f, &self.survival, survival)?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        write!(f, "{}", self.neighborhood.suffix())
+    }
+}
+
+/// Write the counts in `table`, with Hensel letters for the counts where
+/// only some configurations are in `configs`. Whichever of the included or
+/// excluded letters is shorter is written.
+fn write_conditions(f: &mut fmt::Formatter, table: &Counts, configs: Option<Configs>) -> fmt::Result {
+    for n in (0..9u8).filter(|&n| table.contains(n as u32)) {
+        write!(f, "{}", n)?;
+        let some = match configs {
+            Some(configs) => configs.intersection(Configs::all(n)),
+            None => continue,
+        };
+        if some == Configs::all(n) {
+            continue;
+        }
+        let (included, excluded): (String, String) = Configs::letters(n)
+            .partition(|&l| !Configs::letter(n, l).unwrap().intersection(some).is_empty());
+        if excluded.len() < included.len() {
+            write!(f, "-{}", excluded)?;
+        } else {
+            write!(f, "{}", included)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write `counts` as a HROT list, joining runs into ranges: `2-3,5`.
+fn write_count_list(f: &mut fmt::Formatter, counts: &Counts) -> fmt::Result {
+    let counts: Vec<u32> = counts.iter().collect();
+    let mut first = true;
+    for run in counts.split_inclusive(|&n| !counts.contains(&(n + 1))) {
+        if !first {
+            write!(f, ",")?;
+        }
+        first = false;
+        match run {
+            [n] => write!(f, "{}", n)?,
+            _ => write!(f, "{}-{}", run[0], run[run.len() - 1])?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::Universe;
+
+/// `probability` limited to `[0, 1]`, with NaN treated as 0.
+pub(crate) fn clamp_probability(probability: f64) -> f64 {
+    if probability.is_nan() {
+        0.0
+    } else {
+        probability.clamp(0.0, 1.0)
+    }
+}
+
+impl Universe {
+    /// Whether `tick` needs to draw from the generator at all. Keeping the
+    /// deterministic path free of draws leaves it unchanged and fast.
+    pub(crate) fn is_stochastic(&self) -> bool {
+        self.birth_probability < 1.0 || self.survival_probability < 1.0 || self.noise > 0.0
+    }
+
+    /// Undo births and survivals that fail their probability, then flip
+    /// cells at the noise rate, visiting cells row by row. `before` holds
+    /// the states from the start of the generation.
+    pub(crate) fn apply_chance<T: Copy + Into<u8>>(&mut self, before: &[T]) {
+        let dying = if self.rule.states() > 2 { 2 } else { 0 };
+        for (i, was) in before.iter().map(|&was| was.into()).enumerate() {
+            let mut state = self.state(i);
+            if state == 1 {
+                let probability = if was == 1 { self.survival_probability } else { self.birth_probability };
+                if probability < 1.0 && self.rng.next_f64() >= probability {
+                    state = if was == 1 { dying } else { was };
+                    self.write_state(i, state);
+                }
+            }
+            if self.noise > 0.0 && self.rng.next_f64() < self.noise {
+                self.write_state(i, if state == 1 { 0 } else { 1 });
+            }
+        }
+    }
+}
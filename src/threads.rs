@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(all(feature = "threads", target_arch = "wasm32"))]
+use wasm_bindgen::prelude::*;
+
+/// The `initThreadPool(threads)` export from `wasm-bindgen-rayon`, which
+/// starts Web Workers sharing this module's memory and returns a promise.
+#[cfg(all(feature = "threads", target_arch = "wasm32"))]
+pub use wasm_bindgen_rayon::init_thread_pool;
+
+/// Whether rayon has threads to run on. Native targets start their pool on
+/// first use; in the browser it has to be started from JS first.
+static POOL_READY: AtomicBool = AtomicBool::new(cfg!(not(target_arch = "wasm32")));
+
+/// Let `tick` use the Web Workers started by `initThreadPool`. Call it from
+/// the worker that owns the universe, once the promise `initThreadPool`
+/// returned has resolved; until then `tick` stays on the calling thread.
+/// The `threads` build shares its memory between workers, so it only loads
+/// on cross-origin isolated pages, where `SharedArrayBuffer` exists.
+///
+/// Fails on the page's main thread, where rayon would block on atomics
+/// while waiting for its workers, which browsers do not allow.
+#[cfg(all(feature = "threads", target_arch = "wasm32"))]
+#[wasm_bindgen]
+pub fn thread_pool_ready() -> Result<(), JsValue> {
+    let in_worker = js_sys::Reflect::has(&js_sys::global(), &JsValue::from_str("WorkerGlobalScope"))?;
+    if !in_worker {
+        return Err(js_sys::Error::new("the thread pool can only be used from a Web Worker").into());
+    }
+    POOL_READY.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Whether `tick` should split its bands across threads.
+pub(crate) fn use_threads() -> bool {
+    POOL_READY.load(Ordering::Relaxed)
+}
@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use crate::{Cell, Topology, Universe};
+
+/// Side of the square tiles `tick` tracks activity in.
+pub const TILE_SIZE: u32 = 32;
+
+/// What computing one row of tiles found.
+pub(crate) struct Band {
+    /// Whether each tile in the row changed.
+    pub activity: Vec<bool>,
+    /// Indices of the cells that changed, in order.
+    pub changed: Vec<usize>,
+    pub skipped: u32,
+}
+
+impl Universe {
+    /// Number of tile rows and columns covering the board; tiles on the
+    /// bottom and right edges may be smaller.
+    pub(crate) fn tile_grid(&self) -> (u32, u32) {
+        (self.height.div_ceil(TILE_SIZE), self.width.div_ceil(TILE_SIZE))
+    }
+
+    /// The rows and columns of tile `t`, counted row by row.
+    pub(crate) fn tile_bounds(&self, t: usize) -> (Range<u32>, Range<u32>) {
+        let (_, tile_cols) = self.tile_grid();
+        let (tile_row, tile_col) = (t as u32 / tile_cols, t as u32 % tile_cols);
+        let (row, col) = (tile_row * TILE_SIZE, tile_col * TILE_SIZE);
+        (row..(row + TILE_SIZE).min(self.height), col..(col + TILE_SIZE).min(self.width))
+    }
+
+    /// Note that the tile holding cell `i` changed, so it and the tiles
+    /// around it are recomputed next generation.
+    pub(crate) fn mark_tile(&mut self, i: usize) {
+        if self.tile_activity.is_empty() {
+            return;
+        }
+        let (_, tile_cols) = self.tile_grid();
+        let (row, col) = (i as u32 / self.width, i as u32 % self.width);
+        self.tile_activity[((row / TILE_SIZE) * tile_cols + col / TILE_SIZE) as usize] = true;
+    }
+
+    /// Forget which tiles are stable, so the next generation recomputes
+    /// every tile. Needed whenever cells change other than through
+    /// `write_cell` and `write_state`, or the rule or topology changes.
+    pub(crate) fn invalidate_tiles(&mut self) {
+        self.tile_activity.clear();
+    }
+
+    /// Which tiles the next generation has to compute: those that changed
+    /// and those with a cell within `range` of one that did. Activity near
+    /// an edge that wraps wakes every tile within `range` of any edge,
+    /// which covers wherever the topology maps the neighbours to.
+    pub(crate) fn dirty_tiles(&self, range: u32) -> Vec<bool> {
+        let (tile_rows, tile_cols) = self.tile_grid();
+        let count = (tile_rows * tile_cols) as usize;
+        if self.tile_activity.len() != count {
+            return vec![true; count];
+        }
+        let tiles_of = |start: i64, end: i64, size: u32| {
+            let first = start.max(0) as u32 / TILE_SIZE;
+            let last = (end.min(size as i64 - 1).max(0) as u32) / TILE_SIZE;
+            first..=last
+        };
+        let mut dirty = vec![false; count];
+        let mut near_edge = false;
+        for t in (0..count).filter(|&t| self.tile_activity[t]) {
+            let (rows, cols) = self.tile_bounds(t);
+            let (top, bottom) = (rows.start as i64 - range as i64, rows.end as i64 - 1 + range as i64);
+            let (left, right) = (cols.start as i64 - range as i64, cols.end as i64 - 1 + range as i64);
+            near_edge |= top < 0 || left < 0 || bottom >= self.height as i64 || right >= self.width as i64;
+            for tile_row in tiles_of(top, bottom, self.height) {
+                for tile_col in tiles_of(left, right, self.width) {
+                    dirty[(tile_row * tile_cols + tile_col) as usize] = true;
+                }
+            }
+        }
+        if near_edge && self.topology != Topology::Plane {
+            let (height, width, r) = (self.height as i64, self.width as i64, range as i64);
+            for tile_row in 0..tile_rows {
+                for tile_col in 0..tile_cols {
+                    let t = (tile_row * tile_cols + tile_col) as usize;
+                    let (rows, cols) = self.tile_bounds(t);
+                    let edge_rows = (rows.start as i64) < r || rows.end as i64 > height - r;
+                    let edge_cols = (cols.start as i64) < r || cols.end as i64 > width - r;
+                    dirty[t] |= edge_rows || edge_cols;
+                }
+            }
+        }
+        dirty
+    }
+
+    /// Compute the next generation of tile row `tile_row` into `band`,
+    /// which holds those rows of the next board. Only tiles marked in
+    /// `dirty` are computed; the others are copied from the current board.
+    pub(crate) fn tick_band(
+        &self,
+        tile_row: usize,
+        dirty: &[bool],
+        band: &mut [Cell],
+        offsets: &[(i64, i64)],
+        sums: &Option<Vec<u32>>,
+    ) -> Band {
+        let start = tile_row * self.width as usize * TILE_SIZE as usize;
+        let mut result = Band {
+            activity: vec![false; dirty.len()],
+            changed: Vec::new(),
+            skipped: 0,
+        };
+        for (tile_col, &needed) in dirty.iter().enumerate() {
+            let (rows, cols) = self.tile_bounds(tile_row * dirty.len() + tile_col);
+            if !needed {
+                for row in rows {
+                    let (first, len) = (self.get_index(row, cols.start), cols.len());
+                    band[first - start..][..len].copy_from_slice(&self.cells[first..][..len]);
+                }
+                result.skipped += 1;
+                continue;
+            }
+            for row in rows {
+                let counts = self.simd_counts(row, &cols, offsets, sums);
+                for col in cols.clone() {
+                    let i = self.get_index(row, col);
+                    band[i - start] = match &counts {
+                        Some((first, counts)) if col >= *first && ((col - first) as usize) < counts.len() => {
+                            self.rule.next(self.cells[i], counts[(col - first) as usize] as u32)
+                        }
+                        _ => self.next_cell(row, col, offsets, sums),
+                    };
+                    if band[i - start] != self.cells[i] {
+                        result.activity[tile_col] = true;
+                        result.changed.push(i);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Neighbour counts for the interior of `row` within `cols` from the
+    /// SIMD counter, when the rule only needs the 3x3 Moore count.
+    #[cfg(feature = "simd")]
+    fn simd_counts(&self, row: u32, cols: &Range<u32>, offsets: &[(i64, i64)], sums: &Option<Vec<u32>>) -> Option<(u32, Vec<u8>)> {
+        if !offsets.is_empty() || sums.is_some() || !self.rule.is_totalistic() {
+            return None;
+        }
+        self.row_counts(row, cols)
+    }
+
+    #[cfg(not(feature = "simd"))]
+    fn simd_counts(&self, _: u32, _: &Range<u32>, _: &[(i64, i64)], _: &Option<Vec<u32>>) -> Option<(u32, Vec<u8>)> {
+        None
+    }
+}
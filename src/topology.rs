@@ -0,0 +1,118 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+/// A cell coordinate that lies outside the board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CoordinateError {
+    pub row: i64,
+    pub col: i64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl fmt::Display for CoordinateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cell ({}, {}) is outside the {}x{} universe",
+            self.row, self.col, self.width, self.height
+        )
+    }
+}
+
+impl std::error::Error for CoordinateError {}
+
+impl From<CoordinateError> for JsValue {
+    fn from(err: CoordinateError) -> JsValue {
+        js_sys::RangeError::new(&err.to_string()).into()
+    }
+}
+
+/// Index of `(row, col)` in a row-major `width x height` board, or an error
+/// if it lies outside.
+pub(crate) fn checked_index(row: i64, col: i64, width: u32, height: u32) -> Result<usize, CoordinateError> {
+    if (0..height as i64).contains(&row) && (0..width as i64).contains(&col) {
+        Ok((row * width as i64 + col) as usize)
+    } else {
+        Err(CoordinateError { row, col, width, height })
+    }
+}
+
+/// How the edges of a `Universe` are glued together.
+///
+/// Crossing an edge of a wrapped axis brings you back on the opposite edge.
+/// Where noted, the crossing also mirrors the other axis, which is what
+/// gives the non-orientable surfaces their twist.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// Both axes wrap.
+    #[default]
+    Torus = 0,
+    /// Neither axis wraps; everything beyond the edges is dead.
+    Plane = 1,
+    /// Columns wrap left to right; the top and bottom edges are dead.
+    HorizontalCylinder = 2,
+    /// Rows wrap top to bottom; the left and right edges are dead.
+    VerticalCylinder = 3,
+    /// Both axes wrap, and crossing the top or bottom edge mirrors the
+    /// column.
+    KleinBottle = 4,
+    /// Both axes wrap, crossing the top or bottom edge mirrors the column
+    /// and crossing the left or right edge mirrors the row.
+    CrossSurface = 5,
+    /// Both axes wrap, and crossing the top or bottom edge moves the column
+    /// along by the universe's shift.
+    TwistedTorus = 6,
+}
+
+impl Topology {
+    /// Map a possibly out-of-range `(row, col)` onto the board, or `None` if
+    /// it falls off a dead edge.
+    pub fn resolve(self, row: i64, col: i64, width: u32, height: u32, shift: u32) -> Option<(u32, u32)> {
+        let (w, h) = (width as i64, height as i64);
+        let (row_wraps, col_wraps) = (row.div_euclid(h), col.div_euclid(w));
+        let (mut r, mut c) = (row.rem_euclid(h), col.rem_euclid(w));
+        let in_rows = row_wraps == 0;
+        let in_cols = col_wraps == 0;
+
+        match self {
+            Topology::Torus => {}
+            Topology::Plane if !(in_rows && in_cols) => return None,
+            Topology::Plane => {}
+            Topology::HorizontalCylinder if !in_rows => return None,
+            Topology::HorizontalCylinder => {}
+            Topology::VerticalCylinder if !in_cols => return None,
+            Topology::VerticalCylinder => {}
+            Topology::KleinBottle => {
+                if row_wraps % 2 != 0 {
+                    c = w - 1 - c;
+                }
+            }
+            Topology::CrossSurface => {
+                if row_wraps % 2 != 0 {
+                    c = w - 1 - c;
+                }
+                if col_wraps % 2 != 0 {
+                    r = h - 1 - r;
+                }
+            }
+            Topology::TwistedTorus => c = (col + row_wraps * shift as i64).rem_euclid(w),
+        }
+        Some((r as u32, c as u32))
+    }
+
+    /// Whether reaching `(row, col)` crossed edges that mirror the column
+    /// and the row, as `(column mirrored, row mirrored)`. Used to keep
+    /// things that have a direction, like ants, consistent with `resolve`.
+    pub(crate) fn mirrors(self, row: i64, col: i64, width: u32, height: u32) -> (bool, bool) {
+        let (row_wraps, col_wraps) = (row.div_euclid(height as i64), col.div_euclid(width as i64));
+        match self {
+            Topology::KleinBottle => (row_wraps % 2 != 0, false),
+            Topology::CrossSurface => (row_wraps % 2 != 0, col_wraps % 2 != 0),
+            _ => (false, false),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{RuleError, Universe};
+
+/// The direction an ant faces.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Heading {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3,
+}
+
+impl Heading {
+    fn from_quarter_turns(turns: u8) -> Heading {
+        match turns % 4 {
+            0 => Heading::North,
+            1 => Heading::East,
+            2 => Heading::South,
+            _ => Heading::West,
+        }
+    }
+
+    /// One step forward, as `(rows, columns)`.
+    fn delta(self) -> (i64, i64) {
+        match self {
+            Heading::North => (-1, 0),
+            Heading::East => (0, 1),
+            Heading::South => (1, 0),
+            Heading::West => (0, -1),
+        }
+    }
+}
+
+/// What an ant does on a cell of a given colour.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+    /// Carry straight on.
+    None,
+    /// Turn around.
+    UTurn,
+}
+
+impl Turn {
+    fn quarter_turns(self) -> u8 {
+        match self {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::UTurn => 2,
+            Turn::Left => 3,
+        }
+    }
+
+    fn letter(self) -> char {
+        match self {
+            Turn::Left => 'L',
+            Turn::Right => 'R',
+            Turn::None => 'N',
+            Turn::UTurn => 'U',
+        }
+    }
+}
+
+/// A turmite rule: the turn an ant makes on each cell colour, written as
+/// one letter per colour, e.g. `RL` for Langton's ant or `RLR`. Each step
+/// the ant turns, moves the cell it leaves on to the next colour and steps
+/// forward.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AntRule {
+    turns: Vec<Turn>,
+}
+
+impl AntRule {
+    /// Langton's ant, `RL`.
+    pub fn langton() -> AntRule {
+        AntRule {
+            turns: vec![Turn::Right, Turn::Left],
+        }
+    }
+
+    /// Number of cell colours.
+    pub fn colours(&self) -> u8 {
+        self.turns.len() as u8
+    }
+
+    pub fn turn(&self, colour: u8) -> Turn {
+        self.turns[colour as usize % self.turns.len()]
+    }
+
+    /// The colour a cell of `colour` is repainted to, wrapping after the
+    /// last one.
+    pub fn next_colour(&self, colour: u8) -> u8 {
+        ((colour as usize + 1) % self.turns.len()) as u8
+    }
+
+    /// Whether `s` looks like a turmite rule rather than a life-like one.
+    pub(crate) fn matches(s: &str) -> bool {
+        let s = s.trim();
+        !s.is_empty() && s.chars().all(|c| "LRNUlrnu".contains(c))
+    }
+}
+
+impl Default for AntRule {
+    fn default() -> AntRule {
+        AntRule::langton()
+    }
+}
+
+impl FromStr for AntRule {
+    type Err = RuleError;
+
+    /// Parses `L`, `R`, `N` and `U` letters, case-insensitively, with
+    /// between 2 and 255 colours.
+    fn from_str(s: &str) -> Result<AntRule, RuleError> {
+        let turns = s
+            .trim()
+            .chars()
+            .map(|c| match c.to_ascii_uppercase() {
+                'L' => Ok(Turn::Left),
+                'R' => Ok(Turn::Right),
+                'N' => Ok(Turn::None),
+                'U' => Ok(Turn::UTurn),
+                _ => Err(RuleError::Malformed(s.to_string())),
+            })
+            .collect::<Result<Vec<Turn>, RuleError>>()?;
+        if turns.len() < 2 || turns.len() > u8::MAX as usize {
+            return Err(RuleError::Malformed(s.to_string()));
+        }
+        Ok(AntRule { turns })
+    }
+}
+
+impl fmt::Display for AntRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for turn in self.turns.iter() {
+            write!(f, "{}", turn.letter())?;
+        }
+        Ok(())
+    }
+}
+
+/// An ant walking over a `Universe` in turmite mode.
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ant {
+    pub row: u32,
+    pub col: u32,
+    pub heading: Heading,
+    /// Set after crossing a mirroring edge an odd number of times, which
+    /// swaps the ant's left and right.
+    mirrored: bool,
+}
+
+impl Ant {
+    pub fn new(row: u32, col: u32, heading: Heading) -> Ant {
+        Ant {
+            row,
+            col,
+            heading,
+            mirrored: false,
+        }
+    }
+}
+
+impl Universe {
+    /// One step of every ant, in the order they were added. Ants that walk
+    /// off a dead edge are removed.
+    pub(crate) fn tick_turmite(&mut self) {
+        let mut ants = std::mem::take(&mut self.ants);
+        ants.retain_mut(|ant| {
+            let i = self.get_index(ant.row, ant.col);
+            let colour = self.states[i];
+            let mut turns = self.ant_rule.turn(colour).quarter_turns();
+            if ant.mirrored {
+                turns = (4 - turns) % 4;
+            }
+            ant.heading = Heading::from_quarter_turns(ant.heading as u8 + turns);
+            self.write_state(i, self.ant_rule.next_colour(colour));
+            let moved = self.move_ant(ant);
+            if moved {
+                let i = self.get_index(ant.row, ant.col);
+                self.mark_changed(i);
+            }
+            moved
+        });
+        self.ants = ants;
+    }
+
+    /// Step `ant` forward through the topology, returning false if it fell
+    /// off a dead edge.
+    fn move_ant(&self, ant: &mut Ant) -> bool {
+        let (d_row, d_col) = ant.heading.delta();
+        let (row, col) = (ant.row as i64 + d_row, ant.col as i64 + d_col);
+        let (r, c) = match self.topology.resolve(row, col, self.width, self.height, self.shift) {
+            Some(position) => position,
+            None => return false,
+        };
+        let (col_mirrored, row_mirrored) = self.topology.mirrors(row, col, self.width, self.height);
+        if col_mirrored {
+            ant.mirrored = !ant.mirrored;
+            if d_col != 0 {
+                ant.heading = Heading::from_quarter_turns(ant.heading as u8 + 2);
+            }
+        }
+        if row_mirrored {
+            ant.mirrored = !ant.mirrored;
+            if d_row != 0 {
+                ant.heading = Heading::from_quarter_turns(ant.heading as u8 + 2);
+            }
+        }
+        ant.row = r;
+        ant.col = c;
+        true
+    }
+}
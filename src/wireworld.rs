@@ -0,0 +1,72 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{Cell, Universe};
+
+/// A cell of the WireWorld automaton. The values are Golly's WireWorld
+/// states, and are what `Universe::cells()` holds in WireWorld mode.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WireCell {
+    Empty = 0,
+    /// An electron head, which becomes a tail.
+    Head = 1,
+    /// An electron tail, which becomes a conductor.
+    Tail = 2,
+    /// A conductor, which becomes a head next to one or two heads.
+    Conductor = 3,
+}
+
+impl WireCell {
+    /// The rule name that selects WireWorld, as Golly spells it.
+    pub const RULE: &'static str = "WireWorld";
+
+    pub(crate) fn from_state(state: u8) -> WireCell {
+        match state {
+            1 => WireCell::Head,
+            2 => WireCell::Tail,
+            3 => WireCell::Conductor,
+            _ => WireCell::Empty,
+        }
+    }
+
+    /// The next state given the number of neighbouring electron heads.
+    pub fn next(self, heads: u8) -> WireCell {
+        match self {
+            WireCell::Empty => WireCell::Empty,
+            WireCell::Head => WireCell::Tail,
+            WireCell::Tail => WireCell::Conductor,
+            WireCell::Conductor if heads == 1 || heads == 2 => WireCell::Head,
+            WireCell::Conductor => WireCell::Conductor,
+        }
+    }
+
+    /// The state after this one when clicking through them: empty,
+    /// conductor, head, tail and back to empty.
+    pub(crate) fn cycle(self) -> WireCell {
+        match self {
+            WireCell::Empty => WireCell::Conductor,
+            WireCell::Conductor => WireCell::Head,
+            WireCell::Head => WireCell::Tail,
+            WireCell::Tail => WireCell::Empty,
+        }
+    }
+}
+
+impl Universe {
+    /// One generation of WireWorld. Heads are the live `cells`, so the
+    /// usual neighbour count gives the number of neighbouring heads.
+    pub(crate) fn tick_wireworld(&mut self) {
+        let mut future = self.take_next_states();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let i = self.get_index(row, col);
+                future[i] = WireCell::from_state(self.states[i]).next(self.neigh_alive_count(row, col)) as u8;
+            }
+        }
+        for (cell, &state) in self.cells.iter_mut().zip(future.iter()) {
+            *cell = if state == WireCell::Head as u8 { Cell::Alive } else { Cell::Dead };
+        }
+        self.next_states = std::mem::replace(&mut self.states, future);
+    }
+}
@@ -0,0 +1,46 @@
+//! Test suite for the changed-cells delta, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Heading, Universe};
+
+fn sorted(universe: &Universe) -> Vec<u32> {
+    let mut changed = universe.changed().to_vec();
+    changed.sort_unstable();
+    changed
+}
+
+#[test]
+pub fn test_tick_records_flipped_cells() {
+    let mut universe = Universe::with_size(5, 5);
+    for col in 1..4 {
+        universe.toggle_cell(2, col).unwrap();
+    }
+    assert_eq!(sorted(&universe), vec![11, 12, 13]);
+    universe.reset_changed();
+    assert_eq!(universe.changed_count(), 0);
+
+    universe.tick();
+    assert_eq!(sorted(&universe), vec![7, 11, 13, 17]);
+    // Flipping back lists each cell once until the next reset.
+    universe.tick();
+    assert_eq!(sorted(&universe), vec![7, 11, 13, 17]);
+    universe.reset_changed();
+    universe.tick();
+    assert_eq!(universe.changed_count(), 4);
+
+    universe.set_rule("B3/S23/C3").unwrap();
+    universe.reset_changed();
+    universe.tick();
+    // The ends start dying rather than dying outright, which is a change.
+    assert_eq!(universe.get_states()[7], 2);
+    assert_eq!(sorted(&universe), vec![7, 11, 13, 17]);
+}
+
+#[test]
+pub fn test_ants_record_their_cells() {
+    let mut universe = Universe::with_size(5, 5);
+    universe.set_rule("RL").unwrap();
+    universe.add_ant(2, 2, Heading::North).unwrap();
+    universe.reset_changed();
+    universe.tick();
+    assert_eq!(sorted(&universe), vec![12, 13]);
+}
@@ -0,0 +1,202 @@
+//! Test suite for pattern file formats, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::formats::{life106, macrocell, plaintext, rle};
+use wasm_game_of_life::{Cell, HashLife, Pattern, PatternError, Universe};
+
+const GLIDER_RLE: &str = "#N Glider
+#O Richard K. Guy
+#C The smallest, most common, and first discovered spaceship.
+x = 3, y = 3, rule = B3/S23
+bo$2bo$3o!
+";
+
+#[test]
+pub fn test_rle_round_trip() {
+    let pattern = rle::parse(GLIDER_RLE).unwrap();
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+    assert_eq!(pattern.cells, vec![(0,1), (1,2), (2,0), (2,1), (2,2)]);
+    assert_eq!(rle::write(&pattern), GLIDER_RLE);
+    assert_eq!(rle::parse("x = 3, y = 3\nbob$2bo$3o!").unwrap().cells, pattern.cells);
+
+    let universe = Universe::from_pattern(&pattern).unwrap();
+    assert_eq!(universe.get_cells().iter().filter(|&&c| c == Cell::Alive).count(), 5);
+    assert_eq!(universe.to_rle(), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+}
+
+#[test]
+pub fn test_rle_wraps_long_lines() {
+    let mut pattern = rle::parse("x = 200, y = 2\n200o$200o!").unwrap();
+    pattern.cells.retain(|&(_, col)| col % 2 == 0);
+    let text = rle::write(&pattern);
+    assert!(text.lines().all(|line| line.len() <= 70));
+    assert_eq!(rle::parse(&text).unwrap().cells, pattern.cells);
+}
+
+#[test]
+pub fn test_rle_errors() {
+    assert_eq!(rle::parse("#C only a comment"), Err(PatternError::MissingHeader));
+    assert!(matches!(rle::parse("x = three, y = 3\n!"), Err(PatternError::InvalidHeader(_))));
+    assert_eq!(
+        rle::parse("x = 2, y = 1\nbo$\nq!"),
+        Err(PatternError::UnexpectedChar { line: 3, found: 'q' })
+    );
+    assert_eq!(rle::parse("x = 2, y = 1\n3o!"), Err(PatternError::OutOfBounds { row: 0, col: 2 }));
+    assert!(Universe::from_pattern(&rle::parse("x = 1, y = 1, rule = B9/S\no!").unwrap()).is_err());
+
+    // Runs that overflow the coordinates, and sizes too large to allocate.
+    assert!(matches!(rle::parse("x = 2, y = 2\n4294967295b4294967295b!"), Err(PatternError::OutOfBounds { .. })));
+    assert!(matches!(rle::parse("x = 2, y = 2\n4294967295$4294967295$!"), Err(PatternError::OutOfBounds { .. })));
+    assert_eq!(
+        Universe::from_pattern(&rle::parse("x = 70000, y = 70000\no!").unwrap()).err(),
+        Some(PatternError::TooLarge { width: 70000, height: 70000 })
+    );
+}
+
+const GLIDER_CELLS: &str = "!Name: Glider
+! The smallest spaceship.
+!
+.O...
+..O..
+OOO..
+";
+
+#[test]
+pub fn test_plaintext_round_trip() {
+    let pattern = plaintext::parse(GLIDER_CELLS).unwrap();
+    assert_eq!((pattern.width, pattern.height), (5, 3));
+    assert_eq!(pattern.comments, vec!["The smallest spaceship.", ""]);
+    assert_eq!(plaintext::write(&pattern), GLIDER_CELLS);
+    assert_eq!(
+        plaintext::parse("..\nX."),
+        Err(PatternError::UnexpectedChar { line: 2, found: 'X' })
+    );
+}
+
+#[test]
+pub fn test_life106_round_trip() {
+    let text = "#Life 1.06\n#D Glider\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+    let pattern = life106::parse(text).unwrap();
+    assert_eq!((pattern.x, pattern.y, pattern.width, pattern.height), (-1, -1, 3, 3));
+    assert_eq!(life106::write(&pattern), text);
+    assert_eq!(life106::parse("#Life 1.06\n0 zero"), Err(PatternError::InvalidCoordinates { line: 2 }));
+    assert_eq!(life106::parse("0 0"), Err(PatternError::MissingHeader));
+
+    let wide = life106::parse("#Life 1.06\n-2000000000 0\n2000000000 0").unwrap();
+    assert_eq!((wide.x, wide.width, wide.cells[1]), (-2_000_000_000, 4_000_000_001, (0, 4_000_000_000)));
+    assert_eq!(
+        life106::parse("#Life 1.06\n-2147483648 0\n2147483647 0"),
+        Err(PatternError::TooLarge { width: 1 << 32, height: 1 })
+    );
+}
+
+#[test]
+pub fn test_place_pattern_at_offset() {
+    let glider = life106::parse("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+    let mut universe = Universe::with_size(6, 4);
+    // The glider's bounding box wraps past the bottom and right edges.
+    assert!(universe.place_pattern(&glider, 2, 4).is_err());
+    universe.place_pattern_wrapped(&glider, 2, 4).unwrap();
+
+    assert_eq!(universe.to_pattern().cells, vec![(0,0), (0,4), (0,5), (2,5), (3,0)]);
+    assert_eq!(life106::parse(&universe.to_life106()).unwrap().cells.len(), 5);
+}
+
+const GLIDER_MC: &str = "[M2] (golly 4.0)
+#R B3/S23
+$$$$.*$..*$***$
+4 0 0 0 1
+";
+
+#[test]
+pub fn test_macrocell_round_trip() {
+    let mut life = HashLife::from_macrocell(GLIDER_MC).unwrap();
+    assert_eq!(life.population(), 5);
+    // The 16x16 root is centred on the origin, so its SE leaf starts at (0, 0).
+    assert_eq!(life.bounds(), Some(vec![0, 4, 3, 3]));
+    assert_eq!(life.to_macrocell(), "[M2]\n#R B3/S23\n$$$$.*$..*$***$\n4 0 0 0 1\n");
+
+    life.advance(4096).unwrap();
+    let text = life.to_macrocell();
+    let bounds = life.bounds().unwrap();
+    let reread = HashLife::from_macrocell(&text).unwrap();
+    let reread_bounds = reread.bounds().unwrap();
+    assert_eq!(
+        reread.get_cells(reread_bounds[0], reread_bounds[1], 3, 3),
+        life.get_cells(bounds[0], bounds[1], 3, 3)
+    );
+
+    let universe = Universe::from_hashlife(&reread, 9).unwrap();
+    assert_eq!(universe.to_rle(), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+}
+
+#[test]
+pub fn test_macrocell_errors() {
+    assert_eq!(macrocell::parse("4 0 0 0 0").err(), Some(PatternError::MissingHeader));
+    assert_eq!(macrocell::parse("[M2]\n$*x$").err(), Some(PatternError::InvalidNode { line: 2 }));
+    assert_eq!(macrocell::parse("[M2]\n.*$\n5 0 0 0 1").err(), Some(PatternError::InvalidNode { line: 3 }));
+    assert_eq!(macrocell::parse("[M2]\n4 0 0 0 7").err(), Some(PatternError::InvalidNode { line: 2 }));
+
+    let mut life = HashLife::new();
+    life.set_cell(0, 0, true).unwrap();
+    life.set_cell(99_999, 99_999, true).unwrap();
+    assert_eq!(
+        Universe::from_hashlife(&life, 1 << 26).err(),
+        Some(PatternError::TooLarge { width: 100_000, height: 100_000 })
+    );
+}
+
+#[test]
+pub fn test_generations_round_trip() {
+    let mut universe = Universe::from_pattern(&rle::parse("x = 4, y = 4, rule = /2/3\n$.AA$.AA!").unwrap()).unwrap();
+    universe.tick();
+    assert!(universe.get_states().contains(&2));
+
+    let reread = Universe::from_pattern(&rle::parse(&universe.to_rle()).unwrap()).unwrap();
+    assert_eq!(reread.get_states(), universe.get_states());
+    assert_eq!(reread.to_rle(), universe.to_rle());
+}
+
+#[test]
+pub fn test_rle_states_past_24() {
+    let rule = "RL".repeat(15);
+    let text = format!("x = 4, y = 1, rule = {}\nXpA.pE!\n", rule);
+    let pattern = rle::parse(&text).unwrap();
+    assert_eq!(pattern.cells, vec![(0,0), (0,1), (0,3)]);
+    assert_eq!((pattern.state(0), pattern.state(1), pattern.state(2)), (24, 25, 29));
+    assert_eq!(rle::write(&pattern), text);
+
+    let universe = Universe::from_pattern(&pattern).unwrap();
+    assert_eq!(universe.state_count(), 30);
+    assert_eq!(universe.to_rle(), text);
+
+    assert_eq!(rle::parse("x = 2, y = 1\nqAyO!").unwrap().states, vec![49, 255]);
+    assert_eq!(rle::parse("x = 1, y = 1\nyP!"), Err(PatternError::UnexpectedChar { line: 2, found: 'P' }));
+    assert_eq!(rle::parse("x = 1, y = 1\np.!"), Err(PatternError::UnexpectedChar { line: 2, found: 'p' }));
+}
+
+#[test]
+pub fn test_rejects_states_past_the_rule() {
+    let mut universe = Universe::with_size(4, 4);
+    universe.set_rule("/2/3").unwrap();
+    let high = rle::parse("x = 1, y = 1\nyO!").unwrap();
+    assert_eq!(universe.place_pattern(&high, 0, 0), Err(PatternError::InvalidState { state: 255, states: 3 }));
+    assert_eq!(universe.place_pattern_wrapped(&high, 0, 0), Err(PatternError::InvalidState { state: 255, states: 3 }));
+    assert!(universe.get_states().iter().all(|&state| state == 0));
+    universe.tick();
+
+    let wire = rle::parse("x = 2, y = 1, rule = B2/S/C3\nAX!").unwrap();
+    assert_eq!(Universe::from_pattern(&wire).err(), Some(PatternError::InvalidState { state: 24, states: 3 }));
+}
+
+#[test]
+pub fn test_rle_skips_dead_states() {
+    let mut pattern = Pattern { width: 3, height: 1, ..Pattern::default() };
+    pattern.push_cell(0, 0, 0);
+    pattern.push_cell(0, 1, 2);
+    assert_eq!(rle::write(&pattern), "x = 3, y = 1\n.B!\n");
+
+    // Turmite colours past the rule's are refused when placed.
+    let turmite = rle::parse("x = 2, y = 1, rule = RL\nAC!").unwrap();
+    assert_eq!(Universe::from_pattern(&turmite).err(), Some(PatternError::InvalidState { state: 3, states: 2 }));
+}
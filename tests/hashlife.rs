@@ -0,0 +1,78 @@
+//! Test suite for the HashLife engine, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, HashLife, PackedUniverse, Rule, StepError, MAX_STEP};
+
+const GLIDER: [(u32, u32); 5] = [(0,1), (1,2), (2,0), (2,1), (2,2)];
+
+#[test]
+pub fn test_matches_flat_tick() {
+    // R-pentomino, centred so it cannot reach the torus edge in 40 ticks.
+    let r_pentomino = [(30,31), (30,32), (31,30), (31,31), (32,31)];
+    let mut flat = PackedUniverse::new(64, 64);
+    flat.set_cells(&r_pentomino).unwrap();
+
+    let mut life = HashLife::from_cells(64, 64, &flat.get_cells(), Rule::default()).unwrap();
+    for generation in 1..=40 {
+        flat.tick();
+        life.step(0).unwrap();
+        assert_eq!(life.generation(), generation);
+        assert_eq!(life.get_cells(0, 0, 64, 64), flat.get_cells());
+    }
+}
+
+#[test]
+pub fn test_glider_far_future() {
+    let mut cells = vec![Cell::Dead; 9];
+    for (row, col) in GLIDER.iter().cloned() {
+        cells[(row * 3 + col) as usize] = Cell::Alive;
+    }
+    let mut life = HashLife::from_cells(3, 3, &cells, Rule::default()).unwrap();
+
+    // A glider moves one cell diagonally every four generations.
+    life.advance(1_000_000_000).unwrap();
+    assert_eq!(life.generation(), 1_000_000_000);
+    assert_eq!(life.population(), 5);
+    assert_eq!(life.get_cells(250_000_000, 250_000_000, 3, 3), cells);
+}
+
+#[test]
+pub fn test_rejects_b0() {
+    assert!(HashLife::with_rule("B0/S8".parse().unwrap()).is_err());
+}
+
+#[test]
+pub fn test_rejects_steps_past_64_bits() {
+    let mut life = HashLife::from_cells(2, 2, &[Cell::Alive; 4], Rule::default()).unwrap();
+    assert_eq!(life.step(63), Err(StepError::TooLarge));
+    assert_eq!(life.advance(u64::MAX), Err(StepError::TooLarge));
+    assert_eq!(life.generation(), 0);
+
+    life.step(MAX_STEP).unwrap();
+    assert_eq!(life.generation(), 1 << MAX_STEP);
+    assert_eq!(life.population(), 4);
+}
+
+#[test]
+pub fn test_long_thin_board() {
+    // Only the first row of the 65536-wide root is built cell by cell.
+    let mut flat = PackedUniverse::new(65536, 3);
+    flat.set_cells(&[(1,0), (1,1), (1,2), (1,40000), (1,40001), (1,40002)]).unwrap();
+    let mut life = HashLife::from_cells(65536, 3, &flat.get_cells(), Rule::default()).unwrap();
+    assert_eq!(life.population(), 6);
+    assert_eq!(life.get_cells(0, 0, 65536, 3), flat.get_cells());
+
+    life.step(0).unwrap();
+    assert_eq!(life.bounds(), Some(vec![1, 0, 40001, 3]));
+}
+
+#[test]
+pub fn test_set_cell_past_64_bits() {
+    let mut life = HashLife::new();
+    assert_eq!(life.set_cell(i64::MAX, 0, true), Err(StepError::TooLarge));
+    assert_eq!(life.set_cell(0, i64::MIN, true), Err(StepError::TooLarge));
+    assert_eq!(life.population(), 0);
+
+    life.set_cell(1 << 60, -(1 << 60), true).unwrap();
+    assert!(life.is_alive(1 << 60, -(1 << 60)));
+    assert_eq!(life.get_cells(1 << 60, -(1 << 60), 2, 1), vec![Cell::Alive, Cell::Dead]);
+}
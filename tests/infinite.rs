@@ -0,0 +1,60 @@
+//! Test suite for the unbounded universe, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, HashLife, InfiniteUniverse, Rule};
+
+#[test]
+pub fn test_glider_crosses_tiles() {
+    // A glider heading up and to the left, into negative coordinates.
+    let glider = [(0,0), (1,0), (2,0), (0,1), (1,2)];
+    let mut universe = InfiniteUniverse::new();
+    universe.set_cells(&glider);
+    let mut reference = HashLife::new();
+    for (x, y) in glider.iter().cloned() {
+        reference.set_cell(x as i64, y as i64, true).unwrap();
+    }
+
+    for _ in 0..200 {
+        universe.tick();
+        reference.step(0).unwrap();
+    }
+    assert_eq!(universe.population(), 5);
+    assert_eq!(universe.bounds(), Some(vec![-50, -50, 3, 3]));
+    assert_eq!(universe.tile_count(), 1);
+    assert_eq!(universe.get_cells(-60, -60, 80, 80), reference.get_cells(-60, -60, 80, 80));
+}
+
+#[test]
+pub fn test_viewport_and_empty() {
+    let mut universe = InfiniteUniverse::new();
+    universe.set_cell(-1, -1, true);
+    assert_eq!(universe.viewport(-2, -2, 2, 2), vec![0, 0, 0, Cell::Alive as u8]);
+
+    universe.toggle_cell(-1, -1);
+    assert_eq!(universe.tile_count(), 0);
+    assert_eq!(universe.bounds(), None);
+    assert!(InfiniteUniverse::with_rule("B01/S".parse::<Rule>().unwrap()).is_err());
+}
+
+#[test]
+pub fn test_viewport_near_coordinate_limits() {
+    let mut universe = InfiniteUniverse::new();
+    universe.set_cells(&[(i32::MAX, 0), (i32::MIN, 0)]);
+    let right = universe.viewport(i32::MAX - 1, 0, 10, 1);
+    assert_eq!(right, vec![0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let left = universe.viewport(i32::MIN, 0, 2, 1);
+    assert_eq!(left, vec![1, 0]);
+}
+
+#[test]
+pub fn test_tick_clips_at_coordinate_limits() {
+    // A blinker standing on the last column turns horizontal, losing the
+    // cell that would lie past `i32::MAX`.
+    let mut universe = InfiniteUniverse::new();
+    universe.set_cells(&[(i32::MAX, -1), (i32::MAX, 0), (i32::MAX, 1)]);
+    universe.tick();
+    assert_eq!(universe.tile_count(), 1);
+    assert_eq!(universe.bounds(), Some(vec![i32::MAX as i64 - 1, 0, 2, 1]));
+
+    universe.set_cells(&[(i32::MIN, 0)]);
+    assert_eq!(universe.bounds(), Some(vec![i32::MIN as i64, 0, 1 << 32, 1]));
+}
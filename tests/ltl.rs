@@ -0,0 +1,81 @@
+//! Test suite for Larger than Life rules, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, Neighborhood, Rule, Topology, Universe};
+
+/// One generation of `rule` computed by visiting the whole box around
+/// every cell.
+fn naive_tick(universe: &Universe, rule: &Rule, wrap: bool) -> Vec<Cell> {
+    let (width, height) = (universe.width() as i64, universe.height() as i64);
+    let cells = universe.get_cells();
+    let r = rule.range() as i64;
+    let mut next = Vec::new();
+    for row in 0..height {
+        for col in 0..width {
+            let mut count = 0;
+            for d_row in -r..=r {
+                for d_col in -r..=r {
+                    if (d_row, d_col) == (0, 0) && !rule.includes_middle() {
+                        continue;
+                    }
+                    let (n_row, n_col) = (row + d_row, col + d_col);
+                    let inside = (0..height).contains(&n_row) && (0..width).contains(&n_col);
+                    if wrap || inside {
+                        let i = (n_row.rem_euclid(height) * width + n_col.rem_euclid(width)) as usize;
+                        count += cells[i] as u32;
+                    }
+                }
+            }
+            next.push(rule.next(cells[(row * width + col) as usize], count));
+        }
+    }
+    next
+}
+
+#[test]
+pub fn test_parse_bosco() {
+    let bosco: Rule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+    assert_eq!((bosco.neighborhood(), bosco.range()), (Neighborhood::Moore, 5));
+    assert!(bosco.includes_middle());
+    assert!(bosco.survival()[34] && bosco.survival()[58] && !bosco.survival()[59]);
+    assert!(bosco.birth()[45] && !bosco.birth()[33]);
+    assert_eq!(bosco.to_string(), "R5,C0,M1,S34-58,B34-45,NM");
+    assert_eq!(bosco.to_string().parse::<Rule>().unwrap(), bosco);
+}
+
+#[test]
+pub fn test_summed_area_matches_naive() {
+    for &(rule, topology) in [
+        ("R5,C0,M1,S34..58,B34..45,NM", Topology::Torus),
+        ("R5,C0,M1,S34..58,B34..45,NM", Topology::Plane),
+        ("R2,C0,S3-8,B5-7", Topology::Torus),
+    ]
+    .iter()
+    {
+        let mut universe = Universe::random(23, 17, 0.45, 7);
+        universe.set_topology(topology, 0);
+        universe.set_rule(rule).unwrap();
+        let parsed: Rule = rule.parse().unwrap();
+        for _ in 0..4 {
+            let expected = naive_tick(&universe, &parsed, topology == Topology::Torus);
+            universe.tick();
+            assert_eq!(universe.get_cells(), &expected[..], "{} on {:?}", rule, topology);
+        }
+    }
+}
+
+#[test]
+pub fn test_counts_past_old_limit() {
+    // A range 20 box holds 1681 cells, well past what a fixed 512-count
+    // table could describe.
+    let rule: Rule = "R20,C0,M1,S700-1000,B800-900,NM".parse().unwrap();
+    assert!(rule.survival()[1000] && !rule.survival()[1001]);
+    assert_eq!(rule.to_string().parse::<Rule>().unwrap(), rule);
+
+    let mut universe = Universe::random(60, 60, 0.5, 3);
+    universe.set_rule(&rule.to_string()).unwrap();
+    for _ in 0..2 {
+        let expected = naive_tick(&universe, &rule, true);
+        universe.tick();
+        assert_eq!(universe.get_cells(), &expected[..]);
+    }
+}
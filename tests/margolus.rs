@@ -0,0 +1,59 @@
+//! Test suite for Margolus block automata, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, MargolusRule, Mode, RuleError, Topology, Universe};
+
+#[test]
+pub fn test_parse_margolus() {
+    let text = "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15";
+    let rule = text.parse::<MargolusRule>().unwrap();
+    assert_eq!(rule, MargolusRule::billiard_ball());
+    assert_eq!(rule.to_string(), text);
+    assert!(rule.is_reversible());
+    assert!("MS,D0;1;2".parse::<MargolusRule>().is_err());
+    assert!("MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;16".parse::<MargolusRule>().is_err());
+    let mut table = [0; 16];
+    table[3] = 99;
+    assert_eq!(MargolusRule::new(table), Err(RuleError::InvalidBlock(99)));
+    assert!(!MargolusRule::new([0; 16]).unwrap().is_reversible());
+
+    let mut universe = Universe::with_size(4, 4);
+    universe.set_rule(text).unwrap();
+    assert_eq!(universe.mode(), Mode::Margolus);
+    assert_eq!(universe.rule(), text);
+}
+
+#[test]
+pub fn test_billiard_ball_moves_diagonally() {
+    let mut universe = Universe::with_size(8, 8);
+    universe.set_margolus_rule(MargolusRule::billiard_ball());
+    universe.toggle_cell(2, 2).unwrap();
+
+    universe.tick();
+    assert_eq!(universe.phase(), 1);
+    assert_eq!(universe.get_cells()[3 * 8 + 3], Cell::Alive);
+    universe.tick();
+    assert_eq!(universe.phase(), 0);
+    assert_eq!(universe.get_cells()[4 * 8 + 4], Cell::Alive);
+    assert_eq!(universe.get_cells().iter().filter(|&&cell| cell == Cell::Alive).count(), 1);
+}
+
+#[test]
+pub fn test_untick_reverses_critters() {
+    let mut universe = Universe::random(16, 12, 0.3, 7);
+    universe.set_topology(Topology::Torus, 0);
+    universe.set_margolus_rule(MargolusRule::critters());
+    let start = universe.get_cells().to_vec();
+
+    for _ in 0..25 {
+        universe.tick();
+    }
+    assert_ne!(universe.get_cells(), &start[..]);
+    for _ in 0..25 {
+        universe.untick().unwrap();
+    }
+    assert_eq!(universe.phase(), 0);
+    assert_eq!(universe.get_cells(), &start[..]);
+
+    universe.set_margolus_rule(MargolusRule::new([0; 16]).unwrap());
+    assert!(universe.untick().is_err());
+}
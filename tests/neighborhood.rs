@@ -0,0 +1,40 @@
+//! Test suite for neighbourhoods other than 3x3 Moore, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Neighborhood, Topology, Universe};
+
+fn seed_births(rule: &str) -> Vec<(u32, u32)> {
+    let mut universe = Universe::with_size(9, 9);
+    universe.set_topology(Topology::Plane, 0);
+    universe.set_rule(rule).unwrap();
+    universe.set_cells(&[(4,4)]).unwrap();
+    universe.tick();
+    universe.to_pattern().cells
+}
+
+#[test]
+pub fn test_offsets() {
+    assert_eq!(Neighborhood::Moore.offsets(2).len(), 24);
+    assert_eq!(Neighborhood::VonNeumann.offsets(2).len(), 12);
+    assert_eq!(Neighborhood::Hexagonal.offsets(2).len(), 18);
+    assert_eq!(Neighborhood::Hexagonal.offsets(1), vec![(-1,-1), (-1,0), (0,-1), (0,1), (1,0), (1,1)]);
+    for &n in [Neighborhood::Moore, Neighborhood::VonNeumann, Neighborhood::Hexagonal].iter() {
+        for r in 1..5 {
+            assert_eq!(n.offsets(r).len() as u32, n.size(r));
+        }
+    }
+}
+
+#[test]
+pub fn test_tick_neighborhoods() {
+    assert_eq!(seed_births("B1/SV"), vec![(3,4), (4,3), (4,5), (5,4)]);
+    assert_eq!(seed_births("B1/SH"), vec![(3,3), (3,4), (4,3), (4,5), (5,4), (5,5)]);
+    assert_eq!(seed_births("R2,C0,S,B1,NN").len(), 12);
+
+    let mut universe = Universe::with_size(9, 9);
+    universe.set_rule("B1/S").unwrap();
+    universe.set_neighborhood(Neighborhood::Moore, 2).unwrap();
+    assert_eq!(universe.rule(), "R2,C0,S,B1,NM");
+    universe.set_cells(&[(4,4)]).unwrap();
+    universe.tick();
+    assert_eq!(universe.to_pattern().cells.len(), 24);
+}
@@ -0,0 +1,48 @@
+//! Test suite for the bit-packed universe, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, PackedUniverse};
+
+#[test]
+pub fn test_tick_spaceship() {
+    let mut universe = PackedUniverse::new(6, 6);
+    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]).unwrap();
+
+    let mut expected = PackedUniverse::new(6, 6);
+    expected.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]).unwrap();
+
+    universe.tick();
+    assert_eq!(universe.get_cells(), expected.get_cells());
+}
+
+#[test]
+pub fn test_tick_wraps_across_words() {
+    // A blinker straddling the right edge of a 70-wide board, so the
+    // neighbourhood spans the padded last word and wraps to column 0.
+    let mut universe = PackedUniverse::new(70, 5);
+    universe.set_cells(&[(2,69), (2,0), (2,1)]).unwrap();
+    assert_eq!(universe.words_per_row(), 3);
+
+    universe.tick();
+    let mut expected = PackedUniverse::new(70, 5);
+    expected.set_cells(&[(1,0), (2,0), (3,0)]).unwrap();
+    assert_eq!(universe.get_cells(), expected.get_cells());
+
+    universe.tick();
+    assert!(universe.is_alive(2, 69) && universe.is_alive(2, 1));
+    assert_eq!(universe.get_cells().iter().filter(|&&c| c == Cell::Alive).count(), 3);
+}
+
+#[test]
+pub fn test_tick_empty_board() {
+    for &(width, height) in [(0, 4), (4, 0), (0, 0)].iter() {
+        let mut packed = PackedUniverse::new(width, height);
+        packed.tick();
+        assert!(packed.get_cells().is_empty());
+    }
+}
+
+#[test]
+#[should_panic(expected = "too many cells")]
+pub fn test_new_rejects_oversized_board() {
+    PackedUniverse::new(70_000, 70_000);
+}
@@ -0,0 +1,35 @@
+//! Test suite for the banded tick, runs on native targets. Run it with
+//! `--features parallel` as well to cover the multithreaded path.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{PackedUniverse, Topology, Universe};
+
+#[test]
+pub fn test_bands_match_packed_engine() {
+    // Sizes that are not multiples of the band height, with wrapped edges.
+    for &(width, height) in [(300, 200), (97, 131), (33, 1)].iter() {
+        let mut universe = Universe::random(width, height, 0.35, 21);
+        universe.set_topology(Topology::Torus, 0);
+        let mut packed = PackedUniverse::from(&universe);
+        for _ in 0..30 {
+            universe.tick();
+            packed.tick();
+            assert_eq!(universe.get_cells(), &packed.get_cells()[..]);
+        }
+    }
+}
+
+#[test]
+pub fn test_bands_record_changes_in_order() {
+    let mut universe = Universe::random(120, 90, 0.4, 2);
+    universe.reset_changed();
+    let before = universe.get_cells().to_vec();
+    universe.tick();
+    let mut changed: Vec<u32> = (0..before.len() as u32)
+        .filter(|&i| universe.get_cells()[i as usize] != before[i as usize])
+        .collect();
+    // Changes are listed tile by tile, so only their set is fixed.
+    let mut recorded = universe.changed().to_vec();
+    recorded.sort_unstable();
+    changed.sort_unstable();
+    assert_eq!(recorded, changed);
+}
@@ -0,0 +1,26 @@
+//! Test suite for seeded random universes, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, Universe};
+
+fn population(universe: &Universe) -> usize {
+    universe.get_cells().iter().filter(|&&c| c == Cell::Alive).count()
+}
+
+#[test]
+pub fn test_random_is_reproducible() {
+    let a = Universe::random(64, 48, 0.5, 42);
+    let b = Universe::random(64, 48, 0.5, 42);
+    let c = Universe::random(64, 48, 0.5, 43);
+    assert_eq!(a.get_cells(), b.get_cells());
+    assert_ne!(a.get_cells(), c.get_cells());
+    assert_eq!((a.width(), a.height()), (64, 48));
+}
+
+#[test]
+pub fn test_random_density() {
+    assert_eq!(population(&Universe::random(32, 32, 0.0, 7)), 0);
+    assert_eq!(population(&Universe::random(32, 32, 1.0, 7)), 32 * 32);
+
+    let sparse = population(&Universe::random(100, 100, 0.2, 7));
+    assert!((1800..2200).contains(&sparse), "{} live cells", sparse);
+}
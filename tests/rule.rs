@@ -0,0 +1,93 @@
+//! Test suite for rulestring parsing, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, Neighborhood, Rule, RuleError, Universe};
+
+#[test]
+pub fn test_parse_notations() {
+    let highlife: Rule = "B36/S23".parse().unwrap();
+    assert_eq!(highlife, Rule::new(&[3, 6], &[2, 3]));
+    assert_eq!("23/36".parse::<Rule>().unwrap(), highlife);
+    assert_eq!("b36s23".parse::<Rule>().unwrap(), highlife);
+    assert_eq!(highlife.to_string(), "B36/S23");
+
+    let seeds: Rule = "B2/S".parse().unwrap();
+    assert_eq!(seeds.next(Cell::Alive, 2), Cell::Dead);
+    assert_eq!(seeds.next(Cell::Dead, 2), Cell::Alive);
+}
+
+#[test]
+pub fn test_parse_errors() {
+    assert_eq!("B39/S23".parse::<Rule>(), Err(RuleError::InvalidDigit('9')));
+    assert!(matches!("B3".parse::<Rule>(), Err(RuleError::Malformed(_))));
+    assert!(matches!("3/2/3/4".parse::<Rule>(), Err(RuleError::Malformed(_))));
+}
+
+#[test]
+pub fn test_parse_generations() {
+    let brain = Rule::generations(&[2], &[], 3);
+    assert_eq!("/2/3".parse::<Rule>().unwrap(), brain);
+    assert_eq!("B2/S/C3".parse::<Rule>().unwrap(), brain);
+    assert_eq!("345/2/4".parse::<Rule>().unwrap(), "B2/S345/C4".parse().unwrap());
+    assert_eq!(brain.to_string(), "B2/S/C3");
+
+    assert_eq!(brain.next_state(0, 2), 1);
+    assert_eq!(brain.next_state(1, 2), 2);
+    assert_eq!(brain.next_state(2, 2), 0);
+    assert_eq!(brain.next_state(255, 2), 0);
+    assert!(matches!("B2/S/C1".parse::<Rule>(), Err(RuleError::Malformed(_))));
+}
+
+#[test]
+pub fn test_parse_hensel() {
+    let rule: Rule = "B2-a/S12".parse().unwrap();
+    assert!(!rule.is_totalistic());
+    assert_eq!(rule.to_string(), "B2-a/S12");
+    assert_eq!("b2cekin/s12".parse::<Rule>().unwrap(), rule);
+    assert_eq!("B3/S2-i34q".parse::<Rule>().unwrap().to_string(), "B3/S2-i34q");
+    assert_eq!("B3cekainyqjr/S23".parse::<Rule>().unwrap(), Rule::conway());
+    assert_eq!("B2-a/S12/C3".parse::<Rule>().unwrap().to_string(), "B2-a/S12/C3");
+
+    // North and north-east are an adjacent pair (2a), north and south are not.
+    assert_eq!(rule.next_configuration(Cell::Dead, 0b0000_0110), Cell::Dead);
+    assert_eq!(rule.next_configuration(Cell::Dead, 0b0100_0010), Cell::Alive);
+
+    assert_eq!("B1a/S".parse::<Rule>(), Err(RuleError::InvalidLetter(1, 'a')));
+    assert_eq!("B3/S4x".parse::<Rule>(), Err(RuleError::InvalidLetter(4, 'x')));
+    assert_eq!("B0c/S".parse::<Rule>(), Err(RuleError::InvalidLetter(0, 'c')));
+    assert!(matches!("B2-/S".parse::<Rule>(), Err(RuleError::Malformed(_))));
+}
+
+#[test]
+pub fn test_tick_non_totalistic() {
+    let mut universe = Universe::with_size(6, 6);
+    universe.set_rule("B2-a/S").unwrap();
+    universe.set_cells(&[(2,2), (3,3)]).unwrap();
+    universe.tick();
+    assert_eq!(universe.to_pattern().cells, vec![(2,3), (3,2)]);
+
+    universe.clear();
+    universe.set_cells(&[(2,2), (2,3)]).unwrap();
+    universe.tick();
+    assert!(universe.to_pattern().cells.is_empty());
+}
+
+#[test]
+pub fn test_parse_neighborhoods() {
+    let hex: Rule = "B2/S34H".parse().unwrap();
+    assert_eq!((hex.neighborhood(), hex.range()), (Neighborhood::Hexagonal, 1));
+    assert_eq!(hex.to_string(), "B2/S34H");
+    assert_eq!("B2/S013V".parse::<Rule>().unwrap().neighborhood(), Neighborhood::VonNeumann);
+    assert_eq!("B2/S/C3H".parse::<Rule>().unwrap().to_string(), "B2/S/C3H");
+
+    let hrot: Rule = "R2,C0,S2-3,B3,NN".parse().unwrap();
+    assert_eq!((hrot.neighborhood(), hrot.range()), (Neighborhood::VonNeumann, 2));
+    assert_eq!(hrot.to_string(), "R2,C0,S2-3,B3,NN");
+    assert!(hrot.survival()[2] && hrot.survival()[3] && !hrot.survival()[4]);
+    assert_eq!("r3,c2,s2-3,5,10-12,b3".parse::<Rule>().unwrap().to_string(), "R3,C0,S2-3,5,10-12,B3,NM");
+
+    assert_eq!("R0,C0,S2,B3".parse::<Rule>(), Err(RuleError::InvalidRange(0)));
+    assert_eq!("R20,C0,S2,B3".parse::<Rule>().unwrap().range(), 20);
+    assert_eq!("R501,C0,S2,B3".parse::<Rule>(), Err(RuleError::InvalidRange(501)));
+    assert!(matches!("B2a/S3H".parse::<Rule>(), Err(RuleError::Unsupported(_))));
+    assert!(matches!("R2,C0,S2,B3,NX".parse::<Rule>(), Err(RuleError::Malformed(_))));
+}
@@ -0,0 +1,57 @@
+//! Test suite for SIMD neighbour counting, runs on native targets. Run it
+//! with `--features simd` to compare the SIMD counts with the scalar ones.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, PackedUniverse, Topology, Universe};
+
+/// One generation of `rule` on a plane, counting neighbours one by one.
+fn scalar_tick(cells: &[Cell], width: usize, height: usize, birth: &[u8], survival: &[u8]) -> Vec<Cell> {
+    let mut next = vec![Cell::Dead; cells.len()];
+    for row in 0..height as i64 {
+        for col in 0..width as i64 {
+            let mut count = 0;
+            for d_row in -1..=1 {
+                for d_col in -1..=1 {
+                    let (r, c) = (row + d_row, col + d_col);
+                    if (d_row, d_col) != (0, 0) && (0..height as i64).contains(&r) && (0..width as i64).contains(&c) {
+                        count += cells[(r * width as i64 + c) as usize] as u8;
+                    }
+                }
+            }
+            let i = (row * width as i64 + col) as usize;
+            let alive = match cells[i] {
+                Cell::Alive => survival.contains(&count),
+                Cell::Dead => birth.contains(&count),
+            };
+            next[i] = if alive { Cell::Alive } else { Cell::Dead };
+        }
+    }
+    next
+}
+
+#[test]
+pub fn test_counts_match_scalar_tick() {
+    for &(width, height) in [(1, 1), (2, 5), (17, 3), (18, 18), (33, 40), (100, 37)].iter() {
+        for &(rule, birth, survival) in [("B3/S23", &[3][..], &[2, 3][..]), ("B36/S125", &[3, 6], &[1, 2, 5])].iter() {
+            let mut universe = Universe::random(width, height, 0.45, (width * height) as u64);
+            universe.set_topology(Topology::Plane, 0);
+            universe.set_rule(rule).unwrap();
+            for _ in 0..8 {
+                let expected = scalar_tick(universe.get_cells(), width as usize, height as usize, birth, survival);
+                universe.tick();
+                assert_eq!(universe.get_cells(), &expected[..], "{} on {}x{}", rule, width, height);
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_counts_match_packed_engine_on_torus() {
+    let mut universe = Universe::random(131, 67, 0.3, 13);
+    universe.set_topology(Topology::Torus, 0);
+    let mut packed = PackedUniverse::from(&universe);
+    for _ in 0..40 {
+        universe.tick();
+        packed.tick();
+        assert_eq!(universe.get_cells(), &packed.get_cells()[..]);
+    }
+}
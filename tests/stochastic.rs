@@ -0,0 +1,63 @@
+//! Test suite for stochastic rules and noise, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, Universe};
+
+fn alive(universe: &Universe) -> usize {
+    universe.get_cells().iter().filter(|&&cell| cell == Cell::Alive).count()
+}
+
+#[test]
+pub fn test_noisy_runs_repeat_with_seed() {
+    let noisy = |seed: u64| {
+        let mut universe = Universe::random(32, 32, 0.4, 3);
+        universe.set_birth_probability(0.8);
+        universe.set_survival_probability(0.9);
+        universe.set_noise(0.01);
+        universe.set_seed(seed);
+        for _ in 0..20 {
+            universe.tick();
+        }
+        universe.get_cells().to_vec()
+    };
+    assert_eq!(noisy(11), noisy(11));
+    assert_ne!(noisy(11), noisy(12));
+
+    // Certain probabilities and no noise leave the rule deterministic.
+    let mut plain = Universe::random(32, 32, 0.4, 3);
+    let mut certain = Universe::random(32, 32, 0.4, 3);
+    certain.set_seed(99);
+    certain.set_noise(-1.0);
+    certain.set_birth_probability(2.0);
+    assert_eq!((certain.noise(), certain.birth_probability()), (0.0, 1.0));
+    for _ in 0..10 {
+        plain.tick();
+        certain.tick();
+    }
+    assert_eq!(plain.get_cells(), certain.get_cells());
+}
+
+#[test]
+pub fn test_birth_and_survival_probabilities() {
+    let mut universe = Universe::with_size(5, 5);
+    for col in 1..4 {
+        universe.toggle_cell(2, col).unwrap();
+    }
+    universe.set_birth_probability(0.0);
+    universe.tick();
+    // The blinker's centre survives but neither end cell is born.
+    assert_eq!(alive(&universe), 1);
+
+    let mut universe = Universe::random(16, 16, 0.5, 5);
+    universe.set_survival_probability(0.0);
+    let before = universe.get_cells().to_vec();
+    universe.tick();
+    assert!(universe.get_cells().iter().zip(before.iter()).all(|(&now, &was)| now == Cell::Dead || was == Cell::Dead));
+
+    universe.set_rule("B3/S23/C3").unwrap();
+    universe.clear();
+    universe.toggle_cell(0, 0).unwrap();
+    universe.set_noise(1.0);
+    universe.tick();
+    // Every cell flips, including the one left dying.
+    assert_eq!(alive(&universe), 16 * 16);
+}
@@ -0,0 +1,61 @@
+//! Test suite for tile activity tracking, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Topology, Universe};
+
+/// Run `universe` alongside a copy that recomputes every tile, checking
+/// that skipping tiles never changes the result.
+fn assert_matches_full_tick(mut universe: Universe, generations: u32) -> u32 {
+    let topology = universe.topology();
+    let mut full = Universe::from_pattern(&universe.to_pattern()).unwrap();
+    full.set_topology(topology, 0);
+    let mut skipped = 0;
+    for _ in 0..generations {
+        universe.tick();
+        full.set_topology(topology, 0);
+        full.tick();
+        assert_eq!(full.skipped_tiles(), 0);
+        assert_eq!(universe.get_cells(), full.get_cells());
+        skipped += universe.skipped_tiles();
+    }
+    skipped
+}
+
+#[test]
+pub fn test_skipping_matches_full_tick() {
+    for &topology in [Topology::Torus, Topology::KleinBottle, Topology::CrossSurface, Topology::Plane].iter() {
+        // A glider crossing the wrapped edges of a board with partial tiles.
+        let mut universe = Universe::with_size(100, 70);
+        universe.set_topology(topology, 0);
+        universe.insert_glider(64, 94).unwrap();
+        assert!(assert_matches_full_tick(universe, 60) > 0);
+
+        let mut universe = Universe::random(90, 75, 0.3, 4);
+        universe.set_topology(topology, 0);
+        universe.set_rule("R3,C0,M1,S14..19,B14..19,NM").unwrap();
+        assert_matches_full_tick(universe, 20);
+    }
+}
+
+#[test]
+pub fn test_skipped_tile_counts() {
+    let mut universe = Universe::with_size(128, 128);
+    assert_eq!(universe.tile_count(), 16);
+    universe.tick();
+    assert_eq!(universe.skipped_tiles(), 0);
+    universe.tick();
+    assert_eq!(universe.skipped_tiles(), 16);
+
+    // A new block wakes its tile and the eight around it, and once it is
+    // stable nothing needs computing.
+    for &(row, col) in [(48, 48), (48, 49), (49, 48), (49, 49)].iter() {
+        universe.toggle_cell(row, col).unwrap();
+    }
+    universe.tick();
+    assert_eq!(universe.skipped_tiles(), 7);
+    universe.tick();
+    assert_eq!(universe.skipped_tiles(), 16);
+
+    universe.set_noise(0.001);
+    universe.tick();
+    assert_eq!(universe.skipped_tiles(), 0);
+}
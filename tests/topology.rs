@@ -0,0 +1,61 @@
+//! Test suite for boundary topologies, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Cell, Topology, Universe};
+
+fn blank(width: u32, height: u32, topology: Topology, shift: u32) -> Universe {
+    let mut universe = Universe::with_size(width, height);
+    universe.set_topology(topology, shift);
+    universe
+}
+
+fn alive(universe: &Universe) -> Vec<(u32, u32)> {
+    universe.to_pattern().cells
+}
+
+#[test]
+pub fn test_blinker_on_edge() {
+    // A blinker lying along the top edge loses its upper cell on a plane.
+    let mut torus = blank(5, 5, Topology::Torus, 0);
+    torus.set_cells(&[(0,0), (0,1), (0,2)]).unwrap();
+    torus.tick();
+    assert_eq!(alive(&torus), vec![(0,1), (1,1), (4,1)]);
+
+    let mut plane = blank(5, 5, Topology::Plane, 0);
+    plane.set_cells(&[(0,0), (0,1), (0,2)]).unwrap();
+    plane.tick();
+    assert_eq!(alive(&plane), vec![(0,1), (1,1)]);
+
+    let mut cylinder = blank(5, 5, Topology::HorizontalCylinder, 0);
+    cylinder.set_cells(&[(2,4), (2,0), (2,1)]).unwrap();
+    cylinder.tick();
+    assert_eq!(alive(&cylinder), vec![(1,0), (2,0), (3,0)]);
+}
+
+#[test]
+pub fn test_edge_crossings() {
+    let crossings = [
+        (Topology::Plane, None),
+        (Topology::VerticalCylinder, Some((0, 1))),
+        (Topology::KleinBottle, Some((0, 4))),
+        (Topology::CrossSurface, Some((0, 4))),
+        (Topology::TwistedTorus, Some((0, 3))),
+    ];
+    for (topology, expected) in crossings.iter().cloned() {
+        let mut universe = blank(6, 4, topology, 2);
+        // One row past the bottom edge, in column 1.
+        universe.toggle_cell_wrapped(4, 1);
+        assert_eq!(alive(&universe), expected.into_iter().collect::<Vec<_>>(), "{:?}", topology);
+    }
+
+    let mut cross = blank(6, 4, Topology::CrossSurface, 0);
+    cross.toggle_cell_wrapped(1, 6);
+    assert_eq!(alive(&cross), vec![(2, 0)]);
+}
+
+#[test]
+pub fn test_glider_clipped_on_plane() {
+    let mut universe = blank(4, 4, Topology::Plane, 0);
+    universe.insert_glider_wrapped(0, 0);
+    assert_eq!(alive(&universe), vec![(0,1), (1,0), (1,1)]);
+    assert_eq!(universe.get_cells().iter().filter(|&&c| c == Cell::Alive).count(), 3);
+}
@@ -0,0 +1,49 @@
+//! Test suite for Langton's ant and turmites, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{AntRule, Heading, Mode, Topology, Universe};
+
+#[test]
+pub fn test_langtons_ant() {
+    let mut universe = Universe::with_size(10, 10);
+    universe.set_rule("RL").unwrap();
+    assert_eq!(universe.mode(), Mode::Turmite);
+    assert_eq!(universe.rule(), "RL");
+    assert_eq!(universe.state_count(), 2);
+    universe.add_ant(5, 5, Heading::North).unwrap();
+    assert!(universe.add_ant(10, 0, Heading::North).is_err());
+
+    for _ in 0..5 {
+        universe.tick();
+    }
+    let ant = universe.ant(0).unwrap();
+    assert_eq!((ant.row, ant.col, ant.heading), (5, 4, Heading::West));
+    let coloured: Vec<usize> = (0..100).filter(|&i| universe.get_states()[i] != 0).collect();
+    assert_eq!(coloured, vec![56, 65, 66]);
+
+    assert_eq!("rlr".parse::<AntRule>().unwrap().to_string(), "RLR");
+    assert_eq!("RLR".parse::<AntRule>().unwrap().next_colour(2), 0);
+    assert_eq!("RL".repeat(127).parse::<AntRule>().unwrap().next_colour(253), 0);
+    assert!("R".parse::<AntRule>().is_err());
+    assert!("RLX".parse::<AntRule>().is_err());
+}
+
+#[test]
+pub fn test_ants_follow_topology() {
+    let mut universe = Universe::with_size(4, 4);
+    universe.set_rule("LR").unwrap();
+    universe.set_topology(Topology::KleinBottle, 0);
+    universe.add_ant(0, 1, Heading::East).unwrap();
+
+    // Crossing the top edge mirrors the column and the ant's handedness,
+    // so its next left turn is a right turn on the board.
+    universe.tick();
+    let ant = universe.ant(0).unwrap();
+    assert_eq!((ant.row, ant.col, ant.heading), (3, 2, Heading::North));
+    universe.tick();
+    let ant = universe.ant(0).unwrap();
+    assert_eq!((ant.row, ant.col, ant.heading), (3, 3, Heading::East));
+
+    universe.set_topology(Topology::Plane, 0);
+    universe.tick();
+    assert_eq!(universe.ant_count(), 0);
+}
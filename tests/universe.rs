@@ -0,0 +1,106 @@
+//! Test suite for constructing and resizing universes, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::{Anchor, Cell, CoordinateError, Universe};
+
+fn alive(universe: &Universe) -> Vec<(u32, u32)> {
+    universe.to_pattern().cells
+}
+
+#[test]
+pub fn test_with_size_is_empty() {
+    let universe = Universe::with_size(7, 3);
+    assert_eq!((universe.width(), universe.height()), (7, 3));
+    assert!(universe.get_cells().iter().all(|&c| c == Cell::Dead));
+    assert_eq!(Universe::empty().get_cells().len(), 64 * 64);
+}
+
+#[test]
+pub fn test_resize_keeps_pattern() {
+    let mut universe = Universe::with_size(4, 4);
+    universe.set_cells(&[(0,0), (1,2), (3,3)]).unwrap();
+
+    let mut grown = Universe::with_size(4, 4);
+    grown.set_cells(&[(0,0), (1,2), (3,3)]).unwrap();
+    grown.resize(6, 8, Anchor::BottomRight);
+    assert_eq!(alive(&grown), vec![(4,2), (5,4), (7,5)]);
+
+    grown.resize(4, 4, Anchor::BottomRight);
+    assert_eq!(grown.get_cells(), universe.get_cells());
+
+    universe.resize(3, 3, Anchor::TopLeft);
+    assert_eq!(alive(&universe), vec![(0,0), (1,2)]);
+    universe.resize(5, 5, Anchor::Center);
+    assert_eq!(alive(&universe), vec![(1,1), (2,3)]);
+}
+
+#[test]
+pub fn test_out_of_range_coordinates() {
+    let mut universe = Universe::with_size(4, 3);
+    let err = universe.toggle_cell(3, 0).unwrap_err();
+    assert_eq!(err, CoordinateError { row: 3, col: 0, width: 4, height: 3 });
+    assert_eq!(err.to_string(), "cell (3, 0) is outside the 4x3 universe");
+
+    // A failed batch leaves the universe untouched.
+    assert!(universe.set_cells(&[(0,0), (0,4)]).is_err());
+    assert!(universe.insert_glider(0, 0).is_err());
+    assert!(universe.insert_glider(1, 3).is_err());
+    assert!(universe.get_cells().iter().all(|&c| c == Cell::Dead));
+
+    universe.insert_glider(1, 1).unwrap();
+    assert_eq!(alive(&universe), vec![(0,1), (1,2), (2,0), (2,1), (2,2)]);
+    universe.toggle_cell_wrapped(-1, -1);
+    assert!(alive(&universe).contains(&(2,3)));
+}
+
+#[test]
+pub fn test_generations_tick() {
+    let mut universe = Universe::with_size(6, 6);
+    universe.set_rule("/2/3").unwrap();
+    universe.set_cells(&[(2,2), (2,3)]).unwrap();
+    assert_eq!(universe.state_count(), 3);
+
+    universe.tick();
+    let states = universe.get_states();
+    assert_eq!(states[2 * 6 + 2], 2);
+    assert_eq!(states[2 * 6 + 3], 2);
+    // Dying cells are kept in patterns, with their state.
+    let pattern = universe.to_pattern();
+    assert_eq!(pattern.cells, vec![(1,2), (1,3), (2,2), (2,3), (3,2), (3,3)]);
+    assert_eq!(pattern.state(2), 2);
+
+    universe.tick();
+    assert_eq!(universe.get_states()[2 * 6 + 2], 0);
+
+    universe.set_rule("B3/S23").unwrap();
+    assert!(universe.get_states().iter().all(|&s| s <= 1));
+}
+
+#[test]
+pub fn test_tick_reuses_buffers_across_resize() {
+    let mut universe = Universe::random(12, 10, 0.4, 8);
+    universe.tick();
+    universe.tick();
+    universe.resize(20, 16, Anchor::Center);
+    let mut fresh = Universe::with_size(20, 16);
+    for (row, col) in alive(&universe) {
+        fresh.toggle_cell(row, col).unwrap();
+    }
+    for _ in 0..5 {
+        universe.tick();
+        fresh.tick();
+        assert_eq!(alive(&universe), alive(&fresh));
+    }
+}
+
+#[test]
+#[should_panic(expected = "too many cells")]
+pub fn test_with_size_rejects_oversized_board() {
+    Universe::with_size(70_000, 70_000);
+}
+
+#[test]
+#[should_panic(expected = "too many cells")]
+pub fn test_resize_rejects_oversized_board() {
+    let mut universe = Universe::with_size(4, 4);
+    universe.resize(u32::MAX, 2, Anchor::TopLeft);
+}
@@ -8,18 +8,14 @@ wasm_bindgen_test_configure!(run_in_browser);
 
 #[cfg(test)]
 pub fn input_spaceship() -> Universe {
-    let mut universe = Universe::new();
-    universe.set_width(6);
-    universe.set_height(6);
-    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]);
+    let mut universe = Universe::with_size(6, 6);
+    universe.set_cells(&[(1,2), (2,3), (3,1), (3,2), (3,3)]).unwrap();
     universe
 }
 
 pub fn expected_spaceship() -> Universe {
-    let mut universe = Universe::new();
-    universe.set_width(6);
-    universe.set_height(6);
-    universe.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]);
+    let mut universe = Universe::with_size(6, 6);
+    universe.set_cells(&[(2,1), (2,3), (3,2), (3,3), (4,2)]).unwrap();
     universe
 }
 
@@ -31,3 +27,15 @@ pub fn test_tick() {
     input_universe.tick();
     assert_eq!(&input_universe.get_cells(), &expected_universe.get_cells());
 }
+
+// Tests run on the page's main thread, where a `threads` build refuses to
+// hand `tick` to the pool and keeps ticking on its own.
+#[cfg(all(feature = "threads", target_arch = "wasm32"))]
+#[wasm_bindgen_test]
+pub fn test_thread_pool_refused_on_main_thread() {
+    assert!(wasm_game_of_life::thread_pool_ready().is_err());
+
+    let mut input_universe = input_spaceship();
+    input_universe.tick();
+    assert_eq!(&input_universe.get_cells(), &expected_spaceship().get_cells());
+}
@@ -0,0 +1,66 @@
+//! Test suite for WireWorld mode, runs on native targets.
+extern crate wasm_game_of_life;
+use wasm_game_of_life::formats::{plaintext, rle};
+use wasm_game_of_life::{Mode, Topology, Universe, WireCell};
+
+#[test]
+pub fn test_electron_runs_along_wire() {
+    let mut universe = Universe::from_rle("x = 8, y = 1, rule = WireWorld\nBA6C!").unwrap();
+    universe.set_topology(Topology::Plane, 0);
+    assert_eq!(universe.mode(), Mode::WireWorld);
+    assert_eq!(universe.rule(), "WireWorld");
+
+    universe.tick();
+    assert_eq!(universe.get_states(), vec![3, 2, 1, 3, 3, 3, 3, 3]);
+    universe.tick();
+    universe.tick();
+    assert_eq!(universe.get_states(), vec![3, 3, 3, 2, 1, 3, 3, 3]);
+    assert_eq!(universe.to_rle(), "x = 8, y = 1, rule = WireWorld\n3CBA3C!\n");
+}
+
+#[test]
+pub fn test_wire_formats() {
+    let pattern = rle::parse("x = 5, y = 2, rule = WireWorld\nBA3C$.C!").unwrap();
+    assert_eq!(pattern.cells.len(), 6);
+    assert_eq!(pattern.states, vec![2, 1, 3, 3, 3, 3]);
+    assert_eq!(rle::write(&pattern), "x = 5, y = 2, rule = WireWorld\nBA3C$.C!\n");
+
+    let text = plaintext::write(&pattern);
+    assert_eq!(text, "~@###\n.#...\n");
+    let parsed = plaintext::parse(&text).unwrap();
+    assert_eq!(parsed.rule.as_deref(), Some("WireWorld"));
+    assert_eq!(parsed.states, pattern.states);
+}
+
+#[test]
+pub fn test_switch_modes() {
+    let mut universe = Universe::with_size(4, 4);
+    universe.set_cells(&[(1,1), (1,2)]).unwrap();
+    universe.set_mode(Mode::WireWorld);
+    assert_eq!(universe.get_states()[5], WireCell::Conductor as u8);
+    assert!(universe.to_pattern().cells.len() == 2);
+
+    universe.set_wire(1, 1, WireCell::Head).unwrap();
+    universe.toggle_cell(0, 0).unwrap();
+    assert_eq!(universe.get_states()[0], WireCell::Conductor as u8);
+
+    universe.set_mode(Mode::Life);
+    assert_eq!(universe.to_pattern().cells, vec![(0,0), (1,1), (1,2)]);
+}
+
+#[test]
+pub fn test_wires_are_alive_outside_wireworld() {
+    let mut universe = Universe::with_size(4, 4);
+    universe.set_rule("/2/3").unwrap();
+    universe.set_wire(1, 1, WireCell::Conductor).unwrap();
+    universe.set_wire(1, 2, WireCell::Tail).unwrap();
+    assert_eq!(&universe.get_states()[5..7], &[1, 1]);
+    universe.set_wire(1, 2, WireCell::Empty).unwrap();
+    assert_eq!(universe.get_states()[6], 0);
+
+    // A lone live cell dies through its one dying state.
+    universe.tick();
+    assert_eq!(universe.get_states()[5], 2);
+    universe.tick();
+    assert_eq!(universe.get_states()[5], 0);
+}
@@ -1,4 +1,4 @@
-import {Universe, Cell} from "wasm-game-of-life";
+import {Universe, Cell, Mode, Neighborhood, WireCell} from "wasm-game-of-life";
 // Import the WebAssembly memory at the top of the file.
 import { memory } from "wasm-game-of-life/wasm_game_of_life_bg";
 
@@ -6,14 +6,37 @@ const CELL_SIZE = 5; // px
 const GRID_COLOR = "#CCCCCC";
 const DEAD_COLOR = "#FFFFFF";
 const ALIVE_COLOR = "#000000";
+const DYING_COLOR = "#888888";
+const WIRE_COLORS = {
+  [WireCell.Empty]: DEAD_COLOR,
+  [WireCell.Head]: "#3366FF",
+  [WireCell.Tail]: "#FF3333",
+  [WireCell.Conductor]: "#FFAA00",
+};
+// Turmite cell colours, cycled through for rules with more colours.
+const TURMITE_COLORS = [DEAD_COLOR, ALIVE_COLOR, "#33AA33", "#3366FF", "#FFAA00", "#AA33AA"];
+const ANT_COLOR = "#FF3333";
 
 var universe = Universe.new();
 const width = universe.width();
 const height = universe.height();
 
 const canvas = document.getElementById("game-of-life-canvas");
-canvas.height = (CELL_SIZE + 1) * height + 1;
-canvas.width = (CELL_SIZE + 1) * width + 1;
+
+// On the hexagonal neighbourhood a cell touches the cells above-left and
+// below-right of it, so each row is drawn half a cell left of the one above
+// to put neighbours side by side.
+const rowOffset = (row) => {
+  return universe.neighborhood() === Neighborhood.Hexagonal
+    ? (height - 1 - row) * (CELL_SIZE + 1) / 2
+    : 0;
+};
+
+const layout = () => {
+  canvas.height = (CELL_SIZE + 1) * height + 1;
+  canvas.width = (CELL_SIZE + 1) * width + 1 + rowOffset(0);
+};
+layout();
 
 const fps = new class {
   constructor() {
@@ -62,16 +85,18 @@ const drawGrid = () => {
     ctx.beginPath();
     ctx.strokeStyle = GRID_COLOR;
   
-    // Vertical lines.
-    for (let i = 0; i <= width; i++) {
-      ctx.moveTo(i * (CELL_SIZE + 1) + 1, 0);
-      ctx.lineTo(i * (CELL_SIZE + 1) + 1, (CELL_SIZE + 1) * height + 1);
+    // Vertical lines, one row at a time so hexagonal rows can be offset.
+    for (let j = 0; j < height; j++) {
+      for (let i = 0; i <= width; i++) {
+        ctx.moveTo(rowOffset(j) + i * (CELL_SIZE + 1) + 1, j * (CELL_SIZE + 1));
+        ctx.lineTo(rowOffset(j) + i * (CELL_SIZE + 1) + 1, (j + 1) * (CELL_SIZE + 1) + 1);
+      }
     }
   
     // Horizontal lines.
     for (let j = 0; j <= height; j++) {
-      ctx.moveTo(0,                           j * (CELL_SIZE + 1) + 1);
-      ctx.lineTo((CELL_SIZE + 1) * width + 1, j * (CELL_SIZE + 1) + 1);
+      ctx.moveTo(0,            j * (CELL_SIZE + 1) + 1);
+      ctx.lineTo(canvas.width, j * (CELL_SIZE + 1) + 1);
     }
   
     ctx.stroke();
@@ -82,6 +107,40 @@ const getIndex = (row, column) => {
   return row * width + column;
 };
 
+const cellColor = (cells, idx) => {
+  switch (universe.mode()) {
+    case Mode.WireWorld:
+      return WIRE_COLORS[cells[idx]];
+    case Mode.Turmite:
+      return TURMITE_COLORS[cells[idx] % TURMITE_COLORS.length];
+    default:
+      return cells[idx] === Cell.Dead
+        ? DEAD_COLOR
+        : cells[idx] === Cell.Alive
+          ? ALIVE_COLOR
+          : DYING_COLOR;
+  }
+};
+
+const fillCell = (row, col) => {
+  ctx.fillRect(
+    rowOffset(row) + col * (CELL_SIZE + 1) + 1,
+    row * (CELL_SIZE + 1) + 1,
+    CELL_SIZE,
+    CELL_SIZE
+  );
+};
+
+const drawAnts = () => {
+  ctx.fillStyle = ANT_COLOR;
+  for (let i = 0; i < universe.ant_count(); i++) {
+    const ant = universe.ant(i);
+    fillCell(ant.row, ant.col);
+    ant.free();
+  }
+};
+
+// Repaint every cell, and forget the changes recorded since the last frame.
 const drawCells = () => {
   const cellsPtr = universe.cells();
   const cells = new Uint8Array(memory.buffer, cellsPtr, width * height);
@@ -90,22 +149,31 @@ const drawCells = () => {
 
   for (let row = 0; row < height; row++) {
     for (let col = 0; col < width; col++) {
-      const idx = getIndex(row, col);
-
-      ctx.fillStyle = cells[idx] === Cell.Dead
-        ? DEAD_COLOR
-        : ALIVE_COLOR;
-
-      ctx.fillRect(
-        col * (CELL_SIZE + 1) + 1,
-        row * (CELL_SIZE + 1) + 1,
-        CELL_SIZE,
-        CELL_SIZE
-      );
+      ctx.fillStyle = cellColor(cells, getIndex(row, col));
+      fillCell(row, col);
     }
   }
+  drawAnts();
+
+  ctx.stroke();
+  universe.reset_changed();
+};
+
+// Repaint only the cells that changed since the last frame.
+const drawChangedCells = () => {
+  const cells = new Uint8Array(memory.buffer, universe.cells(), width * height);
+  const changed = new Uint32Array(memory.buffer, universe.changed_cells(), universe.changed_count());
+
+  ctx.beginPath();
+
+  for (const idx of changed) {
+    ctx.fillStyle = cellColor(cells, idx);
+    fillCell(Math.floor(idx / width), idx % width);
+  }
+  drawAnts();
 
   ctx.stroke();
+  universe.reset_changed();
 };
 
 let frameId = null;
@@ -149,6 +217,7 @@ const restart_btn = document.getElementById("restart");
 
 restart_btn.addEventListener("click", event => {
   universe = Universe.new();
+  layout();
   drawGrid();
   drawCells();
 });
@@ -163,14 +232,13 @@ clear_btn.addEventListener("click", event => {
 
 const renderLoop = () => {
     fps.render();
-    drawGrid();
     let i = iter;
     while (i>0)
     {
         universe.tick();
         i--;
     }
-    drawCells();
+    drawChangedCells();
 
     frameId = requestAnimationFrame(renderLoop);
 };
@@ -189,8 +257,8 @@ canvas.addEventListener("click", event => {
     const canvasTop = (event.clientY - boundingRect.top) * scaleY;
 
     const row = Math.min(Math.floor(canvasTop / (CELL_SIZE + 1)), height - 1);
-    const col = Math.min(Math.floor(canvasLeft / (CELL_SIZE + 1)), width - 1);
-    universe.insert_glider(row, col);
+    const col = Math.min(Math.floor((canvasLeft - rowOffset(row)) / (CELL_SIZE + 1)), width - 1);
+    universe.insert_glider_wrapped(row, col);
   }
   else {
     const boundingRect = canvas.getBoundingClientRect();
@@ -202,7 +270,7 @@ canvas.addEventListener("click", event => {
   const canvasTop = (event.clientY - boundingRect.top) * scaleY;
 
   const row = Math.min(Math.floor(canvasTop / (CELL_SIZE + 1)), height - 1);
-  const col = Math.min(Math.floor(canvasLeft / (CELL_SIZE + 1)), width - 1);
+  const col = Math.max(Math.min(Math.floor((canvasLeft - rowOffset(row)) / (CELL_SIZE + 1)), width - 1), 0);
     universe.toggle_cell(row, col);
   }
 
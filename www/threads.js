@@ -0,0 +1,98 @@
+// The page for the `threads` build. The universe lives in `tick-worker.js`;
+// this thread asks it for generations and draws the cells it sends back.
+const CELL_SIZE = 5; // px
+const GRID_COLOR = "#CCCCCC";
+const DEAD_COLOR = "#FFFFFF";
+const ALIVE_COLOR = "#000000";
+
+const canvas = document.getElementById("game-of-life-canvas");
+const ctx = canvas.getContext("2d");
+const fpsText = document.getElementById("fps");
+const play_btn = document.getElementById("play-pause");
+const rate = document.getElementById("rate");
+
+// The `threads` build is compiled with shared memory, so it cannot even be
+// instantiated without `SharedArrayBuffer`, which browsers only provide to
+// cross-origin isolated pages. Fall back to the single-threaded page there.
+if (!self.crossOriginIsolated || typeof SharedArrayBuffer === "undefined") {
+  console.warn("No SharedArrayBuffer without cross-origin isolation, loading index.html instead");
+  location.replace("./index.html");
+  throw new Error("threads.html needs cross-origin isolation");
+}
+
+const worker = new Worker("./tick-worker.js", { type: "module" });
+
+let lastFrameTimeStamp = performance.now();
+const renderFps = () => {
+  const now = performance.now();
+  const fps = 1 / (now - lastFrameTimeStamp) * 1000;
+  lastFrameTimeStamp = now;
+  fpsText.textContent = `Frames per Second: ${Math.round(fps)}`;
+};
+
+const drawGrid = (width, height) => {
+  ctx.beginPath();
+  ctx.strokeStyle = GRID_COLOR;
+
+  for (let i = 0; i <= width; i++) {
+    ctx.moveTo(i * (CELL_SIZE + 1) + 1, 0);
+    ctx.lineTo(i * (CELL_SIZE + 1) + 1, (CELL_SIZE + 1) * height + 1);
+  }
+  for (let j = 0; j <= height; j++) {
+    ctx.moveTo(0,                           j * (CELL_SIZE + 1) + 1);
+    ctx.lineTo((CELL_SIZE + 1) * width + 1, j * (CELL_SIZE + 1) + 1);
+  }
+
+  ctx.stroke();
+};
+
+const drawCells = (width, height, cells) => {
+  ctx.beginPath();
+
+  for (let row = 0; row < height; row++) {
+    for (let col = 0; col < width; col++) {
+      ctx.fillStyle = cells[row * width + col] === 0 ? DEAD_COLOR : ALIVE_COLOR;
+      ctx.fillRect(
+        col * (CELL_SIZE + 1) + 1,
+        row * (CELL_SIZE + 1) + 1,
+        CELL_SIZE,
+        CELL_SIZE
+      );
+    }
+  }
+
+  ctx.stroke();
+};
+
+let paused = false;
+let waiting = false;
+
+// Only one batch is in flight at a time, so a slow worker drops frames
+// instead of queueing them up.
+const requestFrame = () => {
+  if (!paused && !waiting) {
+    waiting = true;
+    worker.postMessage({ generations: Number(rate.value) });
+  }
+};
+
+worker.addEventListener("message", ({ data: { width, height, cells } }) => {
+  waiting = false;
+  if (canvas.width !== (CELL_SIZE + 1) * width + 1) {
+    canvas.height = (CELL_SIZE + 1) * height + 1;
+    canvas.width = (CELL_SIZE + 1) * width + 1;
+    drawGrid(width, height);
+  }
+  renderFps();
+  drawCells(width, height, cells);
+  requestAnimationFrame(requestFrame);
+});
+
+play_btn.textContent = "⏸";
+play_btn.addEventListener("click", event => {
+  paused = !paused;
+  play_btn.textContent = paused ? "▶" : "⏸";
+  requestFrame();
+});
+
+requestFrame();
@@ -0,0 +1,26 @@
+// Runs the `threads` build of the universe off the main thread. Rayon waits
+// for its workers with atomics, which browsers only allow inside workers, so
+// the universe and its tick loop live here and the page only draws.
+import init, { Universe, initThreadPool, thread_pool_ready } from "./pkg-threads/wasm_game_of_life.js";
+
+const ready = (async () => {
+  // `threads.js` only starts this worker on cross-origin isolated pages,
+  // where the shared memory the build needs is available.
+  const { memory } = await init();
+  await initThreadPool(navigator.hardwareConcurrency);
+  thread_pool_ready();
+  return { memory, universe: Universe.new() };
+})();
+
+// Each message asks for `generations` ticks and is answered with a copy of
+// the cells, which the page can draw while the next batch runs.
+self.addEventListener("message", async ({ data }) => {
+  const { memory, universe } = await ready;
+  for (let i = 0; i < data.generations; i++) {
+    universe.tick();
+  }
+  const width = universe.width();
+  const height = universe.height();
+  const cells = new Uint8Array(memory.buffer, universe.cells(), width * height).slice();
+  self.postMessage({ width, height, cells }, [cells.buffer]);
+});
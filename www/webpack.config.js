@@ -8,7 +8,21 @@ module.exports = {
     filename: "bootstrap.js",
   },
   mode: "development",
+  // Cross-origin isolation makes `SharedArrayBuffer` available, which the
+  // `threads` build served at `threads.html` needs to tick on Web Workers.
+  devServer: {
+    headers: {
+      "Cross-Origin-Opener-Policy": "same-origin",
+      "Cross-Origin-Embedder-Policy": "require-corp",
+    },
+  },
   plugins: [
-    new CopyWebpackPlugin(['index.html'])
+    new CopyWebpackPlugin([
+      'index.html',
+      'threads.html',
+      'threads.js',
+      'tick-worker.js',
+      { from: 'pkg-threads', to: 'pkg-threads' },
+    ])
   ],
 };